@@ -0,0 +1,459 @@
+use core::{ffi::c_void, fmt::Display, mem::size_of, ptr};
+
+use lib::{
+    acpi::{
+        fix_checksum, set_fadt_dsdt_address, signature_str, Acpi, AcpiError, Rsdp, SdtHeader,
+        DSDT_SIGNATURE, FADT_SIGNATURE, RSDT_SIGNATURE, SSDT_SIGNATURE, XSDT_SIGNATURE,
+    },
+    cstr16, println,
+    uefi::{
+        boot_services::BootServices,
+        config_table::{ACPI_20_TABLE, ACPI_TABLE},
+        helper::AllocatedPool,
+        protocols::{AcpiTableProtocol, FileAttribute, FileMode, FileProtocol},
+        status::StatusError,
+        string::CStr16,
+        MemoryType, SystemTable,
+    },
+};
+
+use crate::{
+    entry::{AcpiOverrides, MAX_ACPI_TABLES},
+    path_to_cstr16, MAX_PATH_LEN,
+};
+
+/// Directory (at the root of the boot volume) the original tables get dumped to
+const DUMP_DIRECTORY: &CStr16 = cstr16!("acpi");
+
+/// An AML table loaded from a file, in ACPI reclaim memory
+type TablePool = AllocatedPool<[u8]>;
+
+#[derive(Debug)]
+pub enum AcpiOverrideError {
+    EfiError(StatusError),
+    Acpi(AcpiError),
+    NoRsdp,
+    InvalidPath,
+    /// Table file is shorter than a header, or its size doesn't match its header
+    InvalidTable,
+    /// A DSDT was given, but there is no FADT to point to it
+    NoFadt,
+    /// Two tables with the given signature were loaded, only SSDTs can be loaded more than once
+    DuplicateTable([u8; 4]),
+}
+
+impl Display for AcpiOverrideError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AcpiOverrideError::EfiError(e) => write!(f, "EFI error: {:?}", e),
+            AcpiOverrideError::Acpi(e) => write!(f, "{}", e),
+            AcpiOverrideError::NoRsdp => write!(f, "no ACPI RSDP in the configuration table"),
+            AcpiOverrideError::InvalidPath => write!(f, "invalid table path"),
+            AcpiOverrideError::InvalidTable => {
+                write!(f, "table file size doesn't match its header")
+            }
+            AcpiOverrideError::NoFadt => write!(f, "no FADT to point to the new DSDT"),
+            AcpiOverrideError::DuplicateTable(s) => {
+                write!(f, "table {} was loaded more than once", signature_str(s))
+            }
+        }
+    }
+}
+
+impl From<StatusError> for AcpiOverrideError {
+    fn from(value: StatusError) -> Self {
+        Self::EfiError(value)
+    }
+}
+
+impl From<AcpiError> for AcpiOverrideError {
+    fn from(value: AcpiError) -> Self {
+        Self::Acpi(value)
+    }
+}
+
+/// Dumps, installs and drops tables as requested by the boot entry. New tables go through the
+/// ACPI Table protocol when the firmware has it, while replacements (including the DSDT) and
+/// dropped tables require rebuilding the XSDT.
+///
+/// `changed` is set as soon as the firmware's tables were changed, which can't be undone, even
+/// if an error follows.
+pub fn apply(
+    overrides: &AcpiOverrides,
+    root: &FileProtocol,
+    system_table: &mut SystemTable,
+    changed: &mut bool,
+) -> Result<(), AcpiOverrideError> {
+    let boot_services = system_table.boot_services();
+    let acpi = current_acpi(system_table)?;
+
+    if overrides.dump {
+        dump_tables(&acpi, root)?;
+        println!("Dumped ACPI tables to \\acpi");
+    }
+
+    let mut tables: [Option<TablePool>; MAX_ACPI_TABLES] = [const { None }; MAX_ACPI_TABLES];
+    for (i, path) in overrides.tables().iter().enumerate() {
+        let table = load_table(path, root, boot_services)?;
+        let new_signature = signature(&table);
+        let duplicate = tables[..i]
+            .iter()
+            .flatten()
+            .any(|t| signature(t) == new_signature);
+        if duplicate && new_signature != *SSDT_SIGNATURE {
+            return Err(AcpiOverrideError::DuplicateTable(new_signature));
+        }
+        println!("Loaded {} from {}", signature_str(&new_signature), path);
+        tables[i] = Some(table);
+    }
+
+    if let Ok(protocol) = boot_services.locate_protocol::<AcpiTableProtocol>() {
+        for slot in tables.iter_mut() {
+            let Some(table) = slot else {
+                continue;
+            };
+            if needs_rebuild(&acpi, signature(table), overrides.drops()) {
+                continue;
+            }
+            protocol.install_acpi_table(table.as_ref())?;
+            *changed = true;
+            // The firmware installed its own copy
+            *slot = None;
+        }
+    }
+
+    if tables.iter().all(Option::is_none) && overrides.drops().is_empty() {
+        return Ok(());
+    }
+
+    // The protocol may have moved the root table
+    let acpi = current_acpi(system_table)?;
+    let has_acpi_10_entry = system_table.config_table(&ACPI_TABLE).is_some();
+    rebuild_xsdt(
+        &acpi,
+        &mut tables,
+        overrides.drops(),
+        has_acpi_10_entry,
+        changed,
+        boot_services,
+    )
+}
+
+fn current_acpi(system_table: &SystemTable) -> Result<Acpi, AcpiOverrideError> {
+    let rsdp = system_table.acpi_rsdp().ok_or(AcpiOverrideError::NoRsdp)?;
+    // Safety: The RSDP comes from the configuration table, and memory is identity-mapped
+    Ok(unsafe { Acpi::from_rsdp(rsdp) }?)
+}
+
+fn signature(table: &TablePool) -> [u8; 4] {
+    let mut signature = [0; 4];
+    signature.copy_from_slice(&table.as_ref()[..4]);
+    signature
+}
+
+/// The protocol only adds tables, it can't replace the DSDT or a table already in the XSDT.
+/// Several SSDTs can coexist, so they are never considered replacements. Tables with a dropped
+/// signature aren't installed through the protocol either: the rebuilt XSDT would drop them too.
+fn needs_rebuild(acpi: &Acpi, signature: [u8; 4], drops: &[[u8; 4]]) -> bool {
+    if signature == *DSDT_SIGNATURE || drops.contains(&signature) {
+        return true;
+    }
+    signature != *SSDT_SIGNATURE && acpi.tables().any(|t| t.signature() == signature)
+}
+
+/// Reads a table from a file into ACPI reclaim memory, and fixes its checksum.
+fn load_table(
+    path: &str,
+    root: &FileProtocol,
+    boot_services: BootServices,
+) -> Result<TablePool, AcpiOverrideError> {
+    let mut path_buf = [0_u16; MAX_PATH_LEN];
+    let path = path_to_cstr16(path, &mut path_buf).ok_or(AcpiOverrideError::InvalidPath)?;
+    let file = root.open(path, FileMode::Read, FileAttribute::default())?;
+    let res = read_table(file, boot_services);
+    // Safety: The file isn't used past this point
+    let _ = unsafe { file.close() };
+    res
+}
+
+fn read_table(
+    file: &FileProtocol,
+    boot_services: BootServices,
+) -> Result<TablePool, AcpiOverrideError> {
+    let size = file.size()? as usize;
+    file.set_position(0)?;
+    if size < size_of::<SdtHeader>() {
+        return Err(AcpiOverrideError::InvalidTable);
+    }
+
+    let mut table = AllocatedPool::<[u8]>::try_new_with_mem_type(
+        boot_services,
+        MemoryType::EfiACPIReclaimMemory,
+        size,
+    )?;
+    let mut read = 0;
+    while read < size {
+        match file.read_bytes(&mut table.as_mut()[read..])? {
+            0 => return Err(AcpiOverrideError::InvalidTable),
+            n => read += n,
+        }
+    }
+
+    // Safety: The table is at least as long as a header, which is packed
+    let header = unsafe { ptr::read_unaligned(table.as_ref().as_ptr() as *const SdtHeader) };
+    if header.length as usize != size {
+        return Err(AcpiOverrideError::InvalidTable);
+    }
+    fix_checksum(table.as_mut());
+    Ok(table)
+}
+
+/// Installs a new XSDT (and RSDP) listing the firmware tables which weren't dropped or
+/// replaced, followed by the loaded tables. Everything handed to the firmware is leaked.
+///
+/// If the firmware also publishes an ACPI 1.0 RSDP, it gets replaced by the new one, which then
+/// points to an RSDT as well. An RSDT can only hold 32-bit addresses: when a table sits above
+/// 4 GiB the ACPI 1.0 entry is removed instead, rather than left pointing to the old tables.
+fn rebuild_xsdt(
+    acpi: &Acpi,
+    tables: &mut [Option<TablePool>],
+    drops: &[[u8; 4]],
+    has_acpi_10_entry: bool,
+    changed: &mut bool,
+    boot_services: BootServices,
+) -> Result<(), AcpiOverrideError> {
+    let loaded = |signature: [u8; 4]| {
+        tables
+            .iter()
+            .flatten()
+            .find(|t| self::signature(t) == signature)
+    };
+
+    // The DSDT is referenced by the FADT, which then needs to be replaced as well
+    let mut fadt_copy = None;
+    if let Some(dsdt) = loaded(*DSDT_SIGNATURE).map(|t| t.as_ref().as_ptr() as u64) {
+        let firmware_fadt = acpi.fadt()?.map(|f| f.table());
+        let fadt = match (loaded(*FADT_SIGNATURE), &firmware_fadt) {
+            (Some(fadt), _) => fadt.as_ref(),
+            (None, Some(fadt)) => fadt.bytes(),
+            (None, None) => return Err(AcpiOverrideError::NoFadt),
+        };
+        let mut copy = AllocatedPool::<[u8]>::try_new_with_mem_type(
+            boot_services,
+            MemoryType::EfiACPIReclaimMemory,
+            fadt.len(),
+        )?;
+        copy.as_mut().copy_from_slice(fadt);
+        set_fadt_dsdt_address(copy.as_mut(), dsdt)?;
+        fadt_copy = Some(copy);
+    }
+
+    let replaced = |signature: [u8; 4]| {
+        drops.contains(&signature)
+            || (signature != *SSDT_SIGNATURE && loaded(signature).is_some())
+            || (signature == *FADT_SIGNATURE && fadt_copy.is_some())
+    };
+    let entries = || {
+        let firmware_tables = acpi
+            .tables()
+            .filter(|t| !replaced(t.signature()))
+            .map(|t| t.address());
+        let loaded_tables = tables
+            .iter()
+            .flatten()
+            .filter(|t| {
+                let signature = signature(t);
+                signature != *DSDT_SIGNATURE
+                    && !(signature == *FADT_SIGNATURE && fadt_copy.is_some())
+            })
+            .map(|t| t.as_ref().as_ptr() as u64);
+        let fadt_table = fadt_copy.iter().map(|t| t.as_ref().as_ptr() as u64);
+        firmware_tables.chain(loaded_tables).chain(fadt_table)
+    };
+
+    for dropped in acpi.tables().filter(|t| drops.contains(&t.signature())) {
+        println!("Dropping {}", signature_str(&dropped.signature()));
+    }
+
+    let header = acpi.root_table().header();
+    let entry_count = entries().count();
+    let xsdt = build_root_table(
+        header,
+        *XSDT_SIGNATURE,
+        entry_count,
+        entries().map(|address| address.to_le_bytes()),
+        boot_services,
+    )?;
+    let rsdt = if has_acpi_10_entry && entries().all(|address| u32::try_from(address).is_ok()) {
+        let entries = entries().map(|address| (address as u32).to_le_bytes());
+        Some(build_root_table(
+            header,
+            *RSDT_SIGNATURE,
+            entry_count,
+            entries,
+            boot_services,
+        )?)
+    } else {
+        None
+    };
+    // The RSDT is allocated from the pool too, which may be above 4 GiB as well
+    let rsdt_address = rsdt
+        .as_ref()
+        .and_then(|t| u32::try_from(t.as_ref().as_ptr() as u64).ok())
+        .unwrap_or(0);
+
+    let mut rsdp = AllocatedPool::<[Rsdp]>::try_new_with_mem_type(
+        boot_services,
+        MemoryType::EfiACPIReclaimMemory,
+        1,
+    )?;
+    rsdp.as_mut()[0] = Rsdp::new(
+        acpi.rsdp().oem_id,
+        rsdt_address,
+        xsdt.as_ref().as_ptr() as u64,
+    );
+    // Safety: The RSDP and everything it references is leaked below
+    unsafe {
+        boot_services
+            .install_configuration_table(&ACPI_20_TABLE, rsdp.as_ref().as_ptr() as *const c_void)
+    }?;
+    *changed = true;
+
+    // The firmware references everything from now on, even if the ACPI 1.0 entry fails below
+    let rsdp = rsdp.leak();
+    xsdt.leak();
+    if let Some(rsdt) = rsdt {
+        rsdt.leak();
+    }
+    if let Some(fadt) = fadt_copy {
+        fadt.leak();
+    }
+    for table in tables.iter_mut().filter_map(Option::take) {
+        table.leak();
+    }
+
+    if has_acpi_10_entry {
+        let table = match rsdt_address {
+            0 => {
+                println!("Tables above 4 GiB, removing the ACPI 1.0 RSDP");
+                ptr::null()
+            }
+            // The first 20 bytes of the new RSDP are a valid ACPI 1.0 RSDP
+            _ => rsdp as *const c_void,
+        };
+        // Safety: The RSDP was leaked, removing the entry needs no memory
+        unsafe { boot_services.install_configuration_table(&ACPI_TABLE, table) }?;
+    }
+    Ok(())
+}
+
+/// Builds an XSDT or RSDT from the header of the firmware's root table and the encoded entries
+fn build_root_table<const N: usize>(
+    mut header: SdtHeader,
+    signature: [u8; 4],
+    entry_count: usize,
+    entries: impl Iterator<Item = [u8; N]>,
+    boot_services: BootServices,
+) -> Result<TablePool, AcpiOverrideError> {
+    let length = size_of::<SdtHeader>() + entry_count * N;
+    let mut table = AllocatedPool::<[u8]>::try_new_with_mem_type(
+        boot_services,
+        MemoryType::EfiACPIReclaimMemory,
+        length,
+    )?;
+    header.signature = signature;
+    header.length = length as u32;
+    header.revision = 1;
+    let (header_bytes, entry_bytes) = table.as_mut().split_at_mut(size_of::<SdtHeader>());
+    // Safety: The buffer is large enough for a header, which is packed
+    unsafe { ptr::write_unaligned(header_bytes.as_mut_ptr() as *mut SdtHeader, header) };
+    for (bytes, entry) in entry_bytes.chunks_exact_mut(N).zip(entries) {
+        bytes.copy_from_slice(&entry);
+    }
+    fix_checksum(table.as_mut());
+    Ok(table)
+}
+
+/// Writes every table (and the DSDT) to `\acpi\<signature>[n].aml`, `n` being the index of
+/// tables sharing a signature (e.g. `SSDT.aml`, `SSDT1.aml`).
+fn dump_tables(acpi: &Acpi, root: &FileProtocol) -> Result<(), AcpiOverrideError> {
+    let dir = root.open(
+        DUMP_DIRECTORY,
+        FileMode::Read | FileMode::Write | FileMode::Create,
+        FileAttribute::Directory,
+    )?;
+
+    let mut res = Ok(());
+    for (i, table) in acpi.tables().chain(acpi.dsdt()?).enumerate() {
+        let signature = table.signature();
+        let index = acpi
+            .tables()
+            .take(i)
+            .filter(|t| t.signature() == signature)
+            .count();
+        let mut name_buf = [0_u16; 16];
+        res = write_file(
+            dir,
+            dump_file_name(signature, index, &mut name_buf),
+            table.bytes(),
+        );
+        if res.is_err() {
+            break;
+        }
+    }
+
+    // Safety: The directory isn't used past this point
+    let _ = unsafe { dir.close() };
+    res
+}
+
+fn dump_file_name(signature: [u8; 4], index: usize, buf: &mut [u16; 16]) -> &CStr16 {
+    let mut len = 0;
+    let mut push = |c: u8| {
+        buf[len] = c as u16;
+        len += 1;
+    };
+
+    for c in signature {
+        push(if c.is_ascii_alphanumeric() { c } else { b'_' });
+    }
+    // Tables are indexed by a u8 at most in practice, 3 digits are enough
+    if index > 0 {
+        let index = index.min(999);
+        for divisor in [100, 10, 1].into_iter().filter(|d| index >= *d) {
+            push(b'0' + (index / divisor % 10) as u8);
+        }
+    }
+    for c in b".aml" {
+        push(*c);
+    }
+
+    // Safety: Only ASCII characters were written, and the buffer was zeroed
+    unsafe { CStr16::from_u16_unsafe(&buf[..=len]) }
+}
+
+fn write_file(dir: &FileProtocol, name: &CStr16, bytes: &[u8]) -> Result<(), AcpiOverrideError> {
+    // Creating a file doesn't truncate it, so any previous dump is deleted first
+    if let Ok(old) = dir.open(
+        name,
+        FileMode::Read | FileMode::Write,
+        FileAttribute::default(),
+    ) {
+        // Safety: The old file isn't used past this point
+        let _ = unsafe { old.delete() };
+    }
+
+    let file = dir.open(
+        name,
+        FileMode::Read | FileMode::Write | FileMode::Create,
+        FileAttribute::default(),
+    )?;
+    let res = file.write_bytes(bytes);
+    // Safety: The file isn't used past this point
+    let close_res = unsafe { file.close() };
+
+    match res? {
+        n if n == bytes.len() => Ok(close_res?),
+        _ => Err(StatusError::VolumeFull.into()),
+    }
+}
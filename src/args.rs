@@ -0,0 +1,67 @@
+use core::fmt::Display;
+
+/// PUB's own command line, from its load options. Set by a firmware boot option or by the image
+/// which started PUB, e.g. `config=test.cfg entry="PamOS (debug)" timeout=0 verbose`.
+#[derive(Default)]
+pub struct Args<'a> {
+    /// Config file used instead of `pub.cfg`
+    pub config: Option<&'a str>,
+    /// Title of the entry booted by default, instead of the config's `default`
+    pub entry: Option<&'a str>,
+    /// Overrides the config's `timeout`
+    pub timeout: Option<usize>,
+    /// Prints more about what PUB is doing
+    pub verbose: bool,
+}
+
+#[derive(Debug)]
+pub enum ArgsError<'a> {
+    UnknownArgument(&'a str),
+    InvalidValue(&'a str),
+}
+
+impl Display for ArgsError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ArgsError::UnknownArgument(arg) => write!(f, "unknown argument {}", arg),
+            ArgsError::InvalidValue(arg) => write!(f, "invalid value in {}", arg),
+        }
+    }
+}
+
+impl<'a> Args<'a> {
+    pub fn parse(args: impl Iterator<Item = &'a str>) -> Result<Self, ArgsError<'a>> {
+        let mut parsed = Self::default();
+        for (i, arg) in args.enumerate() {
+            // The UEFI shell passes the image name first
+            let extension = arg.get(arg.len().saturating_sub(4)..);
+            if i == 0 && extension.is_some_and(|e| e.eq_ignore_ascii_case(".efi")) {
+                continue;
+            }
+
+            match arg
+                .split_once('=')
+                .map(|(key, value)| (key, unquote(value)))
+            {
+                Some(("config", value)) if !value.is_empty() => parsed.config = Some(value),
+                Some(("entry", value)) if !value.is_empty() => parsed.entry = Some(value),
+                Some(("timeout", value)) => {
+                    parsed.timeout = Some(value.parse().map_err(|_| ArgsError::InvalidValue(arg))?)
+                }
+                Some(("config" | "entry", _)) => return Err(ArgsError::InvalidValue(arg)),
+                None if arg == "verbose" => parsed.verbose = true,
+                _ => return Err(ArgsError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Removes the double quotes around a value, if any
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
@@ -0,0 +1,247 @@
+use core::convert::Infallible;
+
+use lib::{
+    acpi::{Acpi, AcpiError, MadtEntry},
+    handoff::{
+        AcpiCpu, AcpiInfo, AcpiInterruptOverride, AcpiIoApic, AcpiPciSegment, BootInfo,
+        BootInfoFlags,
+    },
+    println,
+    uefi::{
+        config_table::MEMORY_ATTRIBUTES_TABLE,
+        helper::{self, AllocatedPool},
+        status::EfiResult,
+        Handle, SystemTable,
+    },
+};
+
+use crate::{
+    entry::{RuntimeHandoff, VirtualMapOwner},
+    loader::KernelFile,
+};
+
+fn base_boot_info(system_table: &SystemTable) -> BootInfo {
+    let mut info = BootInfo::default();
+    info.system_table = system_table.as_ptr() as u64;
+    info.runtime_services = system_table.runtime_services().as_ptr() as u64;
+    info.memory_attributes_table = system_table
+        .config_table(&MEMORY_ATTRIBUTES_TABLE)
+        .map_or(0, |t| t as u64);
+    info.acpi_rsdp = system_table.acpi_rsdp().map_or(0, |t| t as u64);
+    info.smbios = system_table.smbios_entry_point().map_or(0, |t| t as u64);
+    info.smbios3 = system_table.smbios3_entry_point().map_or(0, |t| t as u64);
+    info.device_tree = system_table.device_tree().map_or(0, |t| t as u64);
+    info
+}
+
+/// Fills `info` from the MADT, FADT, HPET and MCFG. Entries past the `AcpiInfo` capacity are
+/// dropped.
+fn fill_acpi_info(info: &mut AcpiInfo, acpi: &Acpi) -> Result<(), AcpiError> {
+    *info = AcpiInfo::default();
+    info.revision = acpi.revision();
+
+    if let Some(madt) = acpi.madt()? {
+        info.local_apic_address = madt.local_apic_address();
+        info.pcat_compat = madt.pcat_compat() as u8;
+        for entry in madt.entries() {
+            match entry {
+                MadtEntry::LocalApic {
+                    processor_uid,
+                    apic_id,
+                    flags,
+                } => push(
+                    &mut info.cpus,
+                    &mut info.cpu_count,
+                    AcpiCpu {
+                        processor_uid: processor_uid as u32,
+                        apic_id: apic_id as u32,
+                        flags,
+                    },
+                ),
+                MadtEntry::LocalX2Apic {
+                    x2apic_id,
+                    flags,
+                    processor_uid,
+                } => push(
+                    &mut info.cpus,
+                    &mut info.cpu_count,
+                    AcpiCpu {
+                        processor_uid,
+                        apic_id: x2apic_id,
+                        flags,
+                    },
+                ),
+                MadtEntry::IoApic {
+                    id,
+                    address,
+                    gsi_base,
+                } => push(
+                    &mut info.io_apics,
+                    &mut info.io_apic_count,
+                    AcpiIoApic {
+                        id: id as u32,
+                        address,
+                        gsi_base,
+                    },
+                ),
+                MadtEntry::InterruptSourceOverride {
+                    bus,
+                    source,
+                    gsi,
+                    flags,
+                } => push(
+                    &mut info.overrides,
+                    &mut info.override_count,
+                    AcpiInterruptOverride {
+                        bus,
+                        source,
+                        flags,
+                        gsi,
+                    },
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(fadt) = acpi.fadt()? {
+        info.sci_interrupt = fadt.sci_interrupt();
+        info.boot_arch_flags = fadt.boot_arch_flags();
+        info.fadt_flags = fadt.flags();
+        info.dsdt = fadt.dsdt_address();
+        info.facs = fadt.facs_address();
+    }
+
+    if let Some(hpet) = acpi.hpet()? {
+        info.hpet_address = hpet.base_address().address;
+    }
+
+    if let Some(mcfg) = acpi.mcfg()? {
+        for e in mcfg.entries() {
+            let segment =
+                AcpiPciSegment::new(e.base_address, e.segment_group, e.start_bus, e.end_bus);
+            push(&mut info.pci_segments, &mut info.pci_segment_count, segment);
+        }
+    }
+
+    Ok(())
+}
+
+fn push<T>(array: &mut [T], count: &mut u32, value: T) {
+    if let Some(slot) = array.get_mut(*count as usize) {
+        *slot = value;
+        *count += 1;
+    }
+}
+
+/// Allocates and fills the `AcpiInfo` handed to the kernel. Errors are reported but don't
+/// prevent booting, the kernel just doesn't get the decoded tables.
+fn build_acpi_info(system_table: &mut SystemTable) -> Option<AllocatedPool<AcpiInfo>> {
+    let Some(rsdp) = system_table.acpi_rsdp() else {
+        println!("Not passing ACPI info: no RSDP");
+        return None;
+    };
+    // Safety: The RSDP comes from the configuration table, and memory is identity-mapped
+    let acpi = match unsafe { Acpi::from_rsdp(rsdp) } {
+        Ok(acpi) => acpi,
+        Err(e) => {
+            println!("Not passing ACPI info: {}", e);
+            return None;
+        }
+    };
+
+    let mut info = AllocatedPool::<AcpiInfo>::try_new(system_table.boot_services()).ok()?;
+    if let Err(e) = fill_acpi_info(info.as_mut(), &acpi) {
+        println!("Not passing ACPI info: {}", e);
+        return None;
+    }
+    Some(info)
+}
+
+/// Jumps to the kernel while keeping boot services running. Returns the kernel's exit code.
+pub fn start_kernel(kernel: &KernelFile, acpi_info: bool, system_table: &mut SystemTable) -> usize {
+    let mut boot_info = base_boot_info(system_table);
+    let acpi_info = if acpi_info {
+        build_acpi_info(system_table)
+    } else {
+        None
+    };
+    if let Some(info) = &acpi_info {
+        boot_info.acpi_info = info.as_ref() as *const AcpiInfo as u64;
+    }
+    // Safety: The kernel was loaded and validated, it receives a valid BootInfo
+    unsafe { kernel.entrypoint()(&boot_info) }
+}
+
+/// Exits boot services, sets up the runtime services mapping and jumps to the kernel. Only
+/// returns if something went wrong before boot services were exited.
+pub fn start_kernel_with_runtime(
+    kernel: &KernelFile,
+    handoff: RuntimeHandoff,
+    acpi_info: bool,
+    image_handle: Handle,
+    system_table: &mut SystemTable,
+) -> EfiResult<Infallible> {
+    let boot_services = system_table.boot_services();
+    let runtime_services = system_table.runtime_services();
+
+    // Every allocation needs to happen before getting the final memory map
+    let mut boot_info = AllocatedPool::<BootInfo>::try_new(boot_services)?;
+    *boot_info.as_mut() = base_boot_info(system_table);
+    let acpi_info = if acpi_info {
+        build_acpi_info(system_table)
+    } else {
+        None
+    };
+    if let Some(info) = &acpi_info {
+        boot_info.as_mut().acpi_info = info.as_ref() as *const AcpiInfo as u64;
+    }
+    let mut memory_map = boot_services.memory_map()?;
+    let virtual_map_buffer = AllocatedPool::<[u8]>::try_new(boot_services, memory_map.capacity())?;
+
+    helper::unregister_services();
+    // Safety: Nothing touches boot services (or the console) past this point
+    if let Err(e) = unsafe { boot_services.exit_boot_services(image_handle, &mut memory_map) } {
+        helper::register_services(system_table);
+        return Err(e);
+    }
+
+    for desc in memory_map.iter_mut().filter(|d| d.is_runtime()) {
+        desc.virtual_start = desc.physical_start.wrapping_add(handoff.offset);
+    }
+
+    let info = boot_info.as_mut();
+    info.flags = BootInfoFlags::BootServicesExited;
+    if handoff.owner == VirtualMapOwner::Loader {
+        let mut virtual_map = memory_map.runtime_descriptors(virtual_map_buffer);
+        // Safety: Boot services are exited, and the physical `RuntimeServices` copy isn't used
+        // anymore if the call succeeds
+        if unsafe { runtime_services.set_virtual_address_map(&mut virtual_map) }.is_ok() {
+            info.flags |= BootInfoFlags::VirtualMapSet;
+            // The system table and runtime services live in runtime regions
+            info.system_table = info.system_table.wrapping_add(handoff.offset);
+            info.runtime_services = info.runtime_services.wrapping_add(handoff.offset);
+        }
+        virtual_map.leak();
+    } else {
+        virtual_map_buffer.leak();
+    }
+
+    info.memory_map = memory_map.as_ptr() as u64;
+    info.memory_map_size = memory_map.size() as u64;
+    info.memory_descriptor_size = memory_map.descriptor_size() as u64;
+    info.memory_descriptor_version = memory_map.descriptor_version();
+    memory_map.leak();
+    if let Some(acpi_info) = acpi_info {
+        acpi_info.leak();
+    }
+
+    let boot_info = boot_info.leak() as *const BootInfo;
+    // Safety: The kernel was loaded and validated, boot services are exited
+    unsafe { kernel.entrypoint()(boot_info) };
+
+    // Nothing to return to once boot services are gone
+    loop {
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,118 @@
+use core::fmt::Display;
+
+use lib::{
+    println,
+    uefi::{
+        boot_services::ImageExit,
+        protocols::{DevicePathProtocol, LoadedImageProtocol, Protocol},
+        status::StatusError,
+        string::CStr16,
+        Handle, SystemTable,
+    },
+};
+
+use crate::{
+    entry::BootEntry,
+    initrd::{Initrd, InitrdError},
+    path_to_cstr16, volume, MAX_PATH_LEN,
+};
+
+const MAX_OPTIONS_LEN: usize = 1024;
+
+#[derive(Debug)]
+pub enum ChainloadError {
+    EfiError(StatusError),
+    InvalidPath,
+    /// Options contain characters which can't be encoded as UCS-2, or are too long
+    InvalidOptions,
+    /// No volume has the requested label
+    VolumeNotFound,
+    Initrd(InitrdError),
+}
+
+impl Display for ChainloadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ChainloadError::EfiError(e) => write!(f, "{}", e),
+            ChainloadError::InvalidPath => write!(f, "invalid image path"),
+            ChainloadError::InvalidOptions => {
+                write!(
+                    f,
+                    "invalid options (max {} characters)",
+                    MAX_OPTIONS_LEN - 1
+                )
+            }
+            ChainloadError::VolumeNotFound => write!(f, "no volume with this label"),
+            ChainloadError::Initrd(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<StatusError> for ChainloadError {
+    fn from(value: StatusError) -> Self {
+        Self::EfiError(value)
+    }
+}
+
+impl From<InitrdError> for ChainloadError {
+    fn from(value: InitrdError) -> Self {
+        Self::Initrd(value)
+    }
+}
+
+/// Loads the EFI application at `path` and starts it with the entry's options. Returns once the
+/// application exits.
+pub fn chainload(
+    path: &str,
+    entry: &BootEntry,
+    boot_device: Handle,
+    image_handle: Handle,
+    system_table: &mut SystemTable,
+) -> Result<ImageExit, ChainloadError> {
+    let boot_services = system_table.boot_services();
+
+    let mut path_buf = [0_u16; MAX_PATH_LEN];
+    let file_path = path_to_cstr16(path, &mut path_buf).ok_or(ChainloadError::InvalidPath)?;
+    let mut options_buf = [0_u16; MAX_OPTIONS_LEN];
+    let options = CStr16::from_str_with_buf(entry.options, &mut options_buf)
+        .ok_or(ChainloadError::InvalidOptions)?;
+
+    let device = match (entry.device, entry.volume) {
+        (Some(device), _) => device,
+        (None, Some(label)) => {
+            volume::find_by_label(label, &boot_services).ok_or(ChainloadError::VolumeNotFound)?
+        }
+        (None, None) => boot_device,
+    };
+    // Served to EFI-stub kernels until they return
+    let initrd = match entry.initrds() {
+        [] => None,
+        paths => Some(volume::with_root(device, &boot_services, |root| {
+            Initrd::install(paths, root, boot_services)
+        })??),
+    };
+    if let Some(initrd) = &initrd {
+        println!("Initrd loaded ({} bytes)", initrd.size());
+    }
+
+    let device_path =
+        DevicePathProtocol::open(device, &boot_services).map_err(StatusError::from)?;
+    let image_path = device_path.with_file_path(file_path, boot_services)?;
+    let image = boot_services.load_image(false, image_handle, Some(&image_path), None)?;
+
+    if !options.is_empty() {
+        match LoadedImageProtocol::open_exclusive(image, &boot_services) {
+            // Safety: The options outlive the image, which is done once `start_image` returns
+            Ok(mut loaded_image) => unsafe { loaded_image.set_load_options(options) },
+            Err(e) => {
+                let _ = boot_services.unload_image(image);
+                return Err(StatusError::from(e).into());
+            }
+        }
+    }
+
+    let exit = boot_services.start_image(image);
+    // The image returned, nobody needs the initrd anymore
+    drop(initrd);
+    Ok(exit)
+}
@@ -0,0 +1,403 @@
+use core::fmt::Display;
+
+use lib::{
+    cstr16,
+    smbios::Uuid,
+    uefi::{
+        boot_services::BootServices,
+        helper::AllocatedPool,
+        protocols::{FileAttribute, FileMode, FileProtocol},
+        status::StatusError,
+        string::CStr16,
+    },
+};
+
+use crate::{
+    entry::{BootEntry, EntryKind, RuntimeHandoff, VirtualMapOwner, DEFAULT_RUNTIME_OFFSET},
+    machine::Machine,
+};
+
+pub const CONFIG_PATH: &CStr16 = cstr16!("pub.cfg");
+const MAX_CONFIG_SIZE: usize = 16 * 1024;
+pub const MAX_ENTRIES: usize = 16;
+const DEFAULT_TIMEOUT: usize = 5;
+/// Time given to PUB to load an entry, before the watchdog resets the machine
+const DEFAULT_WATCHDOG: usize = 5 * 60;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    EfiError(StatusError),
+    TooLarge,
+    InvalidUtf8,
+    /// Line doesn't look like `key = value` or `[title]`
+    Syntax(usize),
+    UnknownKey(usize),
+    InvalidValue(usize),
+    /// Key can be repeated, but was given too many times
+    TooManyValues(usize),
+    /// Entry starting at the given line has no `kernel`, `efi` or `command` key
+    MissingKind(usize),
+    TooManyEntries,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConfigError::EfiError(e) => write!(f, "error reading config file: {:?}", e),
+            ConfigError::TooLarge => {
+                write!(
+                    f,
+                    "config file is too large (max {} bytes)",
+                    MAX_CONFIG_SIZE
+                )
+            }
+            ConfigError::InvalidUtf8 => write!(f, "config file is not valid UTF-8"),
+            ConfigError::Syntax(l) => write!(f, "line {}: syntax error", l),
+            ConfigError::UnknownKey(l) => write!(f, "line {}: unknown key", l),
+            ConfigError::InvalidValue(l) => write!(f, "line {}: invalid value", l),
+            ConfigError::TooManyValues(l) => write!(f, "line {}: key repeated too many times", l),
+            ConfigError::MissingKind(l) => {
+                write!(f, "line {}: entry has no kernel, efi or command", l)
+            }
+            ConfigError::TooManyEntries => {
+                write!(f, "too many entries (max {})", MAX_ENTRIES)
+            }
+        }
+    }
+}
+
+impl From<StatusError> for ConfigError {
+    fn from(value: StatusError) -> Self {
+        Self::EfiError(value)
+    }
+}
+
+/// Raw content of the config file
+pub struct ConfigFile {
+    buffer: AllocatedPool<[u8]>,
+    len: usize,
+}
+
+impl ConfigFile {
+    /// Reads the config file at `path`, usually `CONFIG_PATH`. Returns `Ok(None)` if there is
+    /// none.
+    pub fn read(
+        root: &FileProtocol,
+        path: &CStr16,
+        boot_services: BootServices,
+    ) -> Result<Option<Self>, ConfigError> {
+        let file = match root.open(path, FileMode::Read, FileAttribute::default()) {
+            Ok(f) => f,
+            Err(StatusError::NotFound) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let res = Self::read_file(file, boot_services);
+        // Safety: The file isn't used past this point
+        let _ = unsafe { file.close() };
+        res.map(Some)
+    }
+
+    fn read_file(file: &FileProtocol, boot_services: BootServices) -> Result<Self, ConfigError> {
+        // One extra byte, to detect files that are too large
+        let mut buffer = AllocatedPool::<[u8]>::try_new(boot_services, MAX_CONFIG_SIZE + 1)?;
+        let buf = buffer.as_mut();
+        let mut len = 0;
+        loop {
+            let n = file.read_bytes(&mut buf[len..])?;
+            if n == 0 {
+                break;
+            }
+            len += n;
+            if len > MAX_CONFIG_SIZE {
+                return Err(ConfigError::TooLarge);
+            }
+        }
+
+        Ok(Self { buffer, len })
+    }
+
+    pub fn text(&self) -> Result<&str, ConfigError> {
+        core::str::from_utf8(&self.buffer.as_ref()[..self.len])
+            .map_err(|_| ConfigError::InvalidUtf8)
+    }
+}
+
+/// Parsed configuration. Strings are borrowed from the `ConfigFile`.
+///
+/// ```text
+/// # Comment
+/// timeout = 5
+/// default = PamOS
+/// watchdog = 300
+/// connect = 10
+///
+/// [PamOS]
+/// kernel = kernel.bin
+/// acpi_info = yes
+/// runtime = loader
+/// runtime_offset = 0xffff800000000000
+///
+/// [ACPI tables]
+/// command = acpi
+///
+/// [Windows]
+/// efi = EFI/Microsoft/Boot/bootmgfw.efi
+/// volume = SYSTEM
+/// ```
+pub struct Config<'a> {
+    /// Seconds before booting the default entry, 0 boots it right away
+    pub timeout: usize,
+    /// Title of the default entry, the first entry is used if unset
+    default: Option<&'a str>,
+    /// Seconds before the watchdog resets the machine while loading an entry, 0 disables it
+    pub watchdog: usize,
+    /// Seconds given to connecting every controller at startup, 0 skips it
+    pub connect: usize,
+    entries: [Option<BootEntry<'a>>; MAX_ENTRIES],
+    entry_count: usize,
+}
+
+impl<'a> Config<'a> {
+    /// Configuration used when no config file is found
+    pub fn fallback() -> Self {
+        let mut config = Self::empty();
+        config.entries[0] = Some(BootEntry::fallback());
+        config.entry_count = 1;
+        config
+    }
+
+    fn empty() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            default: None,
+            watchdog: DEFAULT_WATCHDOG,
+            connect: 0,
+            entries: [const { None }; MAX_ENTRIES],
+            entry_count: 0,
+        }
+    }
+
+    pub fn parse(text: &'a str) -> Result<Self, ConfigError> {
+        let mut config = Self::empty();
+        // Line of the `[title]` of the entry being parsed
+        let mut entry_line = 0;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(title) = line.strip_prefix('[') {
+                let title = title
+                    .strip_suffix(']')
+                    .ok_or(ConfigError::Syntax(line_number))?
+                    .trim();
+                config.validate_last_entry(entry_line)?;
+                entry_line = line_number;
+                // Kind gets set by the `kernel`, `efi` or `command` key
+                config.push_entry(BootEntry::new(title, EntryKind::Kernel("")))?;
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(ConfigError::Syntax(line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match config.last_entry_mut() {
+                Some(entry) => entry.set(key, value, line_number)?,
+                // Global keys come before the first entry
+                None => config.set(key, value, line_number)?,
+            }
+        }
+
+        config.validate_last_entry(entry_line)?;
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &'a str, line_number: usize) -> Result<(), ConfigError> {
+        match key {
+            "timeout" => {
+                self.timeout = value
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidValue(line_number))?
+            }
+            "default" => self.default = Some(value),
+            "watchdog" => {
+                self.watchdog = value
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidValue(line_number))?
+            }
+            "connect" => {
+                self.connect = value
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidValue(line_number))?
+            }
+            _ => return Err(ConfigError::UnknownKey(line_number)),
+        }
+
+        Ok(())
+    }
+
+    fn validate_last_entry(&mut self, entry_line: usize) -> Result<(), ConfigError> {
+        match self.last_entry_mut() {
+            Some(BootEntry {
+                kind: EntryKind::Kernel("") | EntryKind::Command("") | EntryKind::Chainload(""),
+                ..
+            }) => Err(ConfigError::MissingKind(entry_line)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds an entry found at runtime. Returns `false` if there is no room left.
+    pub fn add_entry(&mut self, entry: BootEntry<'a>) -> bool {
+        self.push_entry(entry).is_ok()
+    }
+
+    fn push_entry(&mut self, entry: BootEntry<'a>) -> Result<(), ConfigError> {
+        if self.entry_count == MAX_ENTRIES {
+            return Err(ConfigError::TooManyEntries);
+        }
+
+        self.entries[self.entry_count] = Some(entry);
+        self.entry_count += 1;
+        Ok(())
+    }
+
+    fn last_entry_mut(&mut self) -> Option<&mut BootEntry<'a>> {
+        let i = self.entry_count.checked_sub(1)?;
+        self.entries[i].as_mut()
+    }
+
+    /// Removes the entries which aren't meant for `machine`. Returns `false` (and keeps the
+    /// config as is) if no entry would be left.
+    pub fn retain_machine(&mut self, machine: &Machine) -> bool {
+        if !self.entries().any(|e| e.machine.matches(machine)) {
+            return false;
+        }
+
+        let mut kept = 0;
+        for i in 0..self.entry_count {
+            let entry = self.entries[i].take();
+            if entry.is_some_and(|e| e.machine.matches(machine)) {
+                self.entries[kept] = entry;
+                kept += 1;
+            }
+        }
+        self.entry_count = kept;
+        true
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &BootEntry<'a>> {
+        self.entries[..self.entry_count].iter().flatten()
+    }
+
+    /// Index of the default entry. Falls back to the first entry if the `default` key doesn't
+    /// match any entry.
+    pub fn default_index(&self) -> usize {
+        self.default
+            .and_then(|title| self.entry_index(title))
+            .unwrap_or(0)
+    }
+
+    pub fn entry_index(&self, title: &str) -> Option<usize> {
+        self.entries().position(|e| e.title == title)
+    }
+
+    pub fn entry(&self, index: usize) -> Option<&BootEntry<'a>> {
+        self.entries().nth(index)
+    }
+}
+
+impl<'a> BootEntry<'a> {
+    fn set(&mut self, key: &str, value: &'a str, line_number: usize) -> Result<(), ConfigError> {
+        match key {
+            "kernel" => self.kind = EntryKind::Kernel(value),
+            "command" => self.kind = EntryKind::Command(value),
+            "efi" => self.kind = EntryKind::Chainload(value),
+            "volume" => self.volume = Some(value),
+            "options" => self.options = value,
+            "initrd" => {
+                if !self.add_initrd(value) {
+                    return Err(ConfigError::TooManyValues(line_number));
+                }
+            }
+            "acpi_info" => {
+                self.acpi_info = parse_bool(value).ok_or(ConfigError::InvalidValue(line_number))?
+            }
+            "machine_product" => self.machine.product_name = Some(value),
+            "machine_uuid" => {
+                let uuid = Uuid::parse(value).ok_or(ConfigError::InvalidValue(line_number))?;
+                self.machine.uuid = Some(uuid);
+            }
+            "acpi_table" => {
+                if !self.acpi.add_table(value) {
+                    return Err(ConfigError::TooManyValues(line_number));
+                }
+            }
+            "acpi_drop" => {
+                let signature = value
+                    .as_bytes()
+                    .try_into()
+                    .map_err(|_| ConfigError::InvalidValue(line_number))?;
+                if !self.acpi.add_drop(signature) {
+                    return Err(ConfigError::TooManyValues(line_number));
+                }
+            }
+            "acpi_dump" => {
+                self.acpi.dump = parse_bool(value).ok_or(ConfigError::InvalidValue(line_number))?
+            }
+            "runtime" => {
+                let owner = match value {
+                    "none" => {
+                        self.runtime = None;
+                        return Ok(());
+                    }
+                    "loader" => VirtualMapOwner::Loader,
+                    "kernel" => VirtualMapOwner::Kernel,
+                    _ => return Err(ConfigError::InvalidValue(line_number)),
+                };
+                let offset = self.runtime.map_or(DEFAULT_RUNTIME_OFFSET, |r| r.offset);
+                self.runtime = Some(RuntimeHandoff { owner, offset });
+            }
+            "runtime_offset" => {
+                let offset = parse_u64(value).ok_or(ConfigError::InvalidValue(line_number))?;
+                if offset % 0x1000 != 0 {
+                    return Err(ConfigError::InvalidValue(line_number));
+                }
+                match self.runtime.as_mut() {
+                    Some(r) => r.offset = offset,
+                    None => {
+                        // Defaults to the kernel calling SetVirtualAddressMap
+                        self.runtime = Some(RuntimeHandoff {
+                            owner: VirtualMapOwner::Kernel,
+                            offset,
+                        })
+                    }
+                }
+            }
+            _ => return Err(ConfigError::UnknownKey(line_number)),
+        }
+
+        Ok(())
+    }
+}
+
+pub fn parse_bool(s: &str) -> Option<bool> {
+    match s {
+        "yes" | "true" | "on" | "1" => Some(true),
+        "no" | "false" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal number
+pub fn parse_u64(s: &str) -> Option<u64> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
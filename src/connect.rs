@@ -0,0 +1,46 @@
+use lib::uefi::{
+    boot_services::{BootServices, SearchType},
+    event::TimerDelay,
+    status::EfiResult,
+};
+
+/// Outcome of `connect_all`
+pub struct Connected {
+    /// Handles which got at least one driver connected
+    pub handles: usize,
+    /// Whether the pass was cut short, leaving some handles unconnected
+    pub timed_out: bool,
+}
+
+/// Recursively connects every handle to its drivers, like the shell's `connect -r`. Some firmware
+/// only connect the devices needed to start PUB, leaving other disks without a file system.
+///
+/// A single controller can't be interrupted, so `seconds` only stops the pass between handles.
+pub fn connect_all(boot_services: &BootServices, seconds: usize) -> EfiResult<Connected> {
+    let timer = boot_services.create_timer()?;
+    timer.set_timer(TimerDelay::Relative(
+        (seconds as u64).saturating_mul(TimerDelay::SECOND),
+    ))?;
+
+    // Handles created while connecting are children, already connected by the recursive calls
+    let handles = boot_services.locate_handle_buffer(SearchType::AllHandles)?;
+    let mut connected = Connected {
+        handles: 0,
+        timed_out: false,
+    };
+    for handle in handles.as_ref().iter().copied() {
+        if timer.check()? {
+            connected.timed_out = true;
+            break;
+        }
+        // Most handles (images, consoles...) have no driver, which fails with `NotFound`
+        if boot_services
+            .connect_controller(handle, None, None, true)
+            .is_ok()
+        {
+            connected.handles += 1;
+        }
+    }
+
+    Ok(connected)
+}
@@ -0,0 +1,180 @@
+use lib::{smbios::Uuid, uefi::Handle};
+
+use crate::machine::Machine;
+
+/// Runtime regions are mapped right after the higher-half direct map of most kernels
+pub const DEFAULT_RUNTIME_OFFSET: u64 = 0xFFFF_8000_0000_0000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VirtualMapOwner {
+    /// PUB calls `SetVirtualAddressMap` before jumping to the kernel
+    Loader,
+    /// The kernel is expected to call `SetVirtualAddressMap` itself
+    Kernel,
+}
+
+/// How the runtime services are handed to the kernel. Requires exiting boot services before
+/// jumping to the kernel, which means it can't return to PUB.
+#[derive(Clone, Copy, Debug)]
+pub struct RuntimeHandoff {
+    pub owner: VirtualMapOwner,
+    /// Runtime regions get mapped at `physical_start + offset`
+    pub offset: u64,
+}
+
+pub const MAX_ACPI_TABLES: usize = 8;
+pub const MAX_ACPI_DROPS: usize = 8;
+pub const MAX_INITRDS: usize = 4;
+
+/// Changes made to the firmware ACPI tables before starting the kernel
+#[derive(Clone, Copy, Debug)]
+pub struct AcpiOverrides<'a> {
+    /// Paths of the AML tables to install, relative to the root of the boot volume
+    tables: [&'a str; MAX_ACPI_TABLES],
+    table_count: usize,
+    /// Signatures of the firmware tables to remove
+    drops: [[u8; 4]; MAX_ACPI_DROPS],
+    drop_count: usize,
+    /// Dump the original tables to the boot volume before changing anything
+    pub dump: bool,
+}
+
+impl<'a> AcpiOverrides<'a> {
+    pub const fn new() -> Self {
+        Self {
+            tables: [""; MAX_ACPI_TABLES],
+            table_count: 0,
+            drops: [[0; 4]; MAX_ACPI_DROPS],
+            drop_count: 0,
+            dump: false,
+        }
+    }
+
+    /// Returns `false` if there is no room left for the table
+    pub fn add_table(&mut self, path: &'a str) -> bool {
+        let Some(slot) = self.tables.get_mut(self.table_count) else {
+            return false;
+        };
+        *slot = path;
+        self.table_count += 1;
+        true
+    }
+
+    /// Returns `false` if there is no room left for the signature
+    pub fn add_drop(&mut self, signature: [u8; 4]) -> bool {
+        let Some(slot) = self.drops.get_mut(self.drop_count) else {
+            return false;
+        };
+        *slot = signature;
+        self.drop_count += 1;
+        true
+    }
+
+    pub fn tables(&self) -> &[&'a str] {
+        &self.tables[..self.table_count]
+    }
+
+    pub fn drops(&self) -> &[[u8; 4]] {
+        &self.drops[..self.drop_count]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table_count == 0 && self.drop_count == 0 && !self.dump
+    }
+}
+
+/// Restricts an entry to some machines, as identified by SMBIOS. Empty filters match every
+/// machine.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MachineFilter<'a> {
+    pub product_name: Option<&'a str>,
+    pub uuid: Option<Uuid>,
+}
+
+impl MachineFilter<'_> {
+    pub const fn new() -> Self {
+        Self {
+            product_name: None,
+            uuid: None,
+        }
+    }
+
+    pub fn matches(&self, machine: &Machine) -> bool {
+        let product_matches = self
+            .product_name
+            .is_none_or(|p| machine.product_name == Some(p));
+        let uuid_matches = self.uuid.is_none_or(|u| machine.uuid == Some(u));
+        product_matches && uuid_matches
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum EntryKind<'a> {
+    /// Path of the kernel ELF file, relative to the root of the boot volume
+    Kernel(&'a str),
+    /// Shell command line, run by PUB instead of booting anything
+    Command(&'a str),
+    /// Path of an EFI application (boot manager, UEFI shell...), started by the firmware. PUB
+    /// goes back to the menu if it exits.
+    Chainload(&'a str),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BootEntry<'a> {
+    pub title: &'a str,
+    pub kind: EntryKind<'a>,
+    /// `None` if boot services should be kept running (the kernel may then return to PUB)
+    pub runtime: Option<RuntimeHandoff>,
+    /// Pass the decoded ACPI tables (`AcpiInfo`) to the kernel
+    pub acpi_info: bool,
+    pub acpi: AcpiOverrides<'a>,
+    /// Machines the entry is shown on
+    pub machine: MachineFilter<'a>,
+    /// Label of the volume holding the chainloaded image, the boot volume if unset
+    pub volume: Option<&'a str>,
+    /// Volume the entry was discovered on, takes precedence over `volume`
+    pub device: Option<Handle>,
+    /// Load options (command line) of the chainloaded image
+    pub options: &'a str,
+    /// Paths of the initrds served to a chainloaded EFI-stub kernel, on the image's volume
+    initrds: [&'a str; MAX_INITRDS],
+    initrd_count: usize,
+}
+
+impl<'a> BootEntry<'a> {
+    pub const fn new(title: &'a str, kind: EntryKind<'a>) -> Self {
+        Self {
+            title,
+            kind,
+            runtime: None,
+            acpi_info: false,
+            acpi: AcpiOverrides::new(),
+            machine: MachineFilter::new(),
+            volume: None,
+            device: None,
+            options: "",
+            initrds: [""; MAX_INITRDS],
+            initrd_count: 0,
+        }
+    }
+
+    /// Returns `false` if there is no room left for the initrd
+    pub fn add_initrd(&mut self, path: &'a str) -> bool {
+        let Some(slot) = self.initrds.get_mut(self.initrd_count) else {
+            return false;
+        };
+        *slot = path;
+        self.initrd_count += 1;
+        true
+    }
+
+    pub fn initrds(&self) -> &[&'a str] {
+        &self.initrds[..self.initrd_count]
+    }
+}
+
+impl BootEntry<'static> {
+    pub const fn fallback() -> Self {
+        Self::new("PamOS", EntryKind::Kernel("kernel.bin"))
+    }
+}
@@ -0,0 +1,183 @@
+use core::{fmt::Display, mem::ManuallyDrop};
+
+use lib::{
+    guid,
+    uefi::{
+        boot_services::BootServices,
+        helper::AllocatedPool,
+        protocols::{
+            BufferLoadFile2, DevicePathProtocol, FileAttribute, FileMode, FileProtocol,
+            LoadFile2Protocol, Protocol, VendorMediaDevicePath,
+        },
+        status::StatusError,
+        Guid, Handle,
+    },
+};
+
+use crate::{entry::MAX_INITRDS, path_to_cstr16, MAX_PATH_LEN};
+
+/// `LINUX_EFI_INITRD_MEDIA_GUID`, EFI-stub kernels look for a `LoadFile2` protocol on a vendor
+/// device path with this GUID to load their initrd
+const INITRD_MEDIA_GUID: Guid = guid!("5568E427-68FC-4F3D-AC74-CA555231CC68");
+static INITRD_DEVICE_PATH: VendorMediaDevicePath = VendorMediaDevicePath::new(INITRD_MEDIA_GUID);
+
+/// Concatenated cpio archives are aligned on 4 bytes
+const INITRD_ALIGN: usize = 4;
+
+#[derive(Debug)]
+pub enum InitrdError {
+    EfiError(StatusError),
+    InvalidPath,
+    /// Another initrd is already installed, e.g. by a bootloader which started PUB
+    AlreadyInstalled,
+}
+
+impl Display for InitrdError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            InitrdError::EfiError(e) => write!(f, "error loading the initrd: {}", e),
+            InitrdError::InvalidPath => write!(f, "invalid initrd path"),
+            InitrdError::AlreadyInstalled => write!(f, "an initrd is already installed"),
+        }
+    }
+}
+
+impl From<StatusError> for InitrdError {
+    fn from(value: StatusError) -> Self {
+        Self::EfiError(value)
+    }
+}
+
+/// Initrd served to EFI-stub kernels through `LoadFile2`, uninstalled once dropped
+pub struct Initrd {
+    handle: Handle,
+    // Both are in use by the firmware while installed, and are leaked if uninstalling fails
+    protocol: ManuallyDrop<AllocatedPool<BufferLoadFile2>>,
+    data: ManuallyDrop<AllocatedPool<[u8]>>,
+    boot_services: BootServices,
+}
+
+impl Initrd {
+    /// Loads the files at `paths` (concatenated, if there are several of them) and installs the
+    /// initrd protocol.
+    pub fn install(
+        paths: &[&str],
+        root: &FileProtocol,
+        boot_services: BootServices,
+    ) -> Result<Self, InitrdError> {
+        let mut files: [Option<&FileProtocol>; MAX_INITRDS] = [None; MAX_INITRDS];
+        let res = open_files(paths, root, &mut files)
+            .and_then(|_| read_files(files.iter().flatten(), boot_services));
+        for file in files.iter().flatten() {
+            // Safety: The file isn't used past this point
+            let _ = unsafe { file.close() };
+        }
+        let data = res?;
+
+        // Safety: The data is only freed once the protocol is uninstalled
+        let protocol = unsafe { BufferLoadFile2::new(data.as_ref()) };
+        let protocol = AllocatedPool::try_new_with(boot_services, protocol)?;
+
+        let interfaces = [
+            (
+                &DevicePathProtocol::GUID,
+                &*INITRD_DEVICE_PATH as *const DevicePathProtocol as *const _,
+            ),
+            (
+                &LoadFile2Protocol::GUID,
+                protocol.as_ref().as_protocol() as *const LoadFile2Protocol as *const _,
+            ),
+        ];
+        // Safety: The device path is static, and the protocol is uninstalled before being freed
+        let handle = match unsafe {
+            boot_services.install_multiple_protocol_interfaces(None, &interfaces)
+        } {
+            Ok(handle) => handle,
+            Err(StatusError::AlreadyStarted) => return Err(InitrdError::AlreadyInstalled),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            handle,
+            protocol: ManuallyDrop::new(protocol),
+            data: ManuallyDrop::new(data),
+            boot_services,
+        })
+    }
+
+    /// Size of the (concatenated) initrd in bytes
+    pub fn size(&self) -> usize {
+        self.data.as_ref().len()
+    }
+}
+
+impl Drop for Initrd {
+    fn drop(&mut self) {
+        let interfaces = [
+            (
+                &DevicePathProtocol::GUID,
+                &*INITRD_DEVICE_PATH as *const DevicePathProtocol as *const _,
+            ),
+            (
+                &LoadFile2Protocol::GUID,
+                self.protocol.as_ref().as_protocol() as *const LoadFile2Protocol as *const _,
+            ),
+        ];
+        // Safety: The kernel returned, so nobody is loading the initrd anymore
+        let res = unsafe {
+            self.boot_services
+                .uninstall_multiple_protocol_interfaces(self.handle, &interfaces)
+        };
+
+        if res.is_ok() {
+            // Safety: Not installed anymore, and not used past this point
+            unsafe {
+                ManuallyDrop::drop(&mut self.protocol);
+                ManuallyDrop::drop(&mut self.data);
+            }
+        }
+    }
+}
+
+fn open_files<'r>(
+    paths: &[&str],
+    root: &'r FileProtocol,
+    files: &mut [Option<&'r FileProtocol>],
+) -> Result<(), InitrdError> {
+    for (path, file) in paths.iter().zip(files.iter_mut()) {
+        let mut path_buf = [0_u16; MAX_PATH_LEN];
+        let path = path_to_cstr16(path, &mut path_buf).ok_or(InitrdError::InvalidPath)?;
+        *file = Some(root.open(path, FileMode::Read, FileAttribute::default())?);
+    }
+    Ok(())
+}
+
+/// Reads the files one after the other, each one starting on a 4 bytes boundary
+fn read_files<'f>(
+    files: impl Iterator<Item = &'f &'f FileProtocol> + Clone,
+    boot_services: BootServices,
+) -> Result<AllocatedPool<[u8]>, InitrdError> {
+    let mut total: usize = 0;
+    for file in files.clone() {
+        total = total.next_multiple_of(INITRD_ALIGN) + file.size()? as usize;
+    }
+
+    let mut data = AllocatedPool::<[u8]>::try_new(boot_services, total)?;
+    let buf = data.as_mut();
+    // Pools aren't zeroed, and padding must be
+    buf.fill(0);
+    let mut offset: usize = 0;
+    for file in files {
+        offset = offset.next_multiple_of(INITRD_ALIGN);
+        let end = offset + file.size()? as usize;
+        file.set_position(0)?;
+        while offset < end {
+            match file.read_bytes(&mut buf[offset..end])? {
+                0 => return Err(StatusError::EndOfFile.into()),
+                n => offset += n,
+            }
+        }
+    }
+
+    Ok(data)
+}
@@ -0,0 +1,401 @@
+mod fadt;
+mod hpet;
+mod madt;
+mod mcfg;
+
+use core::{
+    ffi::c_void,
+    fmt::Display,
+    mem::size_of,
+    ptr::{self, read_unaligned},
+    slice,
+};
+
+pub use fadt::*;
+pub use hpet::*;
+pub use madt::*;
+pub use mcfg::*;
+
+const RSDP_SIGNATURE: &[u8; 8] = b"RSD PTR ";
+pub const RSDT_SIGNATURE: &[u8; 4] = b"RSDT";
+pub const XSDT_SIGNATURE: &[u8; 4] = b"XSDT";
+pub const DSDT_SIGNATURE: &[u8; 4] = b"DSDT";
+pub const SSDT_SIGNATURE: &[u8; 4] = b"SSDT";
+/// Size of the ACPI 1.0 part of the RSDP, covered by the first checksum
+const RSDP_V1_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Copy)]
+pub enum AcpiError {
+    InvalidRsdpSignature,
+    InvalidRsdpChecksum,
+    /// Length of an ACPI 2.0+ RSDP is shorter than the structure
+    InvalidRsdpLength(u32),
+    /// Checksum of the table with the given signature doesn't match
+    InvalidChecksum([u8; 4]),
+    /// Table with the given signature is shorter than its fixed fields
+    TableTooShort([u8; 4]),
+    /// An address doesn't fit in a 32-bit field of the table with the given signature
+    AddressTooHigh([u8; 4]),
+    NoRootTable,
+}
+
+impl Display for AcpiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AcpiError::InvalidRsdpSignature => write!(f, "invalid RSDP signature"),
+            AcpiError::InvalidRsdpChecksum => write!(f, "invalid RSDP checksum"),
+            AcpiError::InvalidRsdpLength(len) => write!(f, "invalid RSDP length ({} bytes)", len),
+            AcpiError::InvalidChecksum(s) => {
+                write!(f, "invalid checksum for table {}", signature_str(s))
+            }
+            AcpiError::TableTooShort(s) => write!(f, "table {} is too short", signature_str(s)),
+            AcpiError::AddressTooHigh(s) => write!(
+                f,
+                "address above 4 GiB doesn't fit in table {}",
+                signature_str(s)
+            ),
+            AcpiError::NoRootTable => write!(f, "RSDP doesn't point to an RSDT or XSDT"),
+        }
+    }
+}
+
+/// Returns the signature as a string, or "????" if it isn't ASCII.
+pub fn signature_str(signature: &[u8]) -> &str {
+    core::str::from_utf8(signature)
+        .ok()
+        .filter(|s| s.is_ascii())
+        .unwrap_or("????")
+}
+
+fn checksum_valid(bytes: &[u8]) -> bool {
+    bytes.iter().fold(0_u8, |acc, b| acc.wrapping_add(*b)) == 0
+}
+
+/// Offset of the checksum byte in an SDT header
+const SDT_CHECKSUM_OFFSET: usize = 9;
+
+fn checksum(bytes: &[u8]) -> u8 {
+    0_u8.wrapping_sub(bytes.iter().fold(0_u8, |acc, b| acc.wrapping_add(*b)))
+}
+
+/// Updates the checksum of a table (header included), so that its bytes add up to 0. Returns
+/// `false` if `table` is too short to have a header.
+pub fn fix_checksum(table: &mut [u8]) -> bool {
+    if table.len() < size_of::<SdtHeader>() {
+        return false;
+    }
+    table[SDT_CHECKSUM_OFFSET] = 0;
+    table[SDT_CHECKSUM_OFFSET] = checksum(table);
+    true
+}
+
+/// Reads a `T` at `offset` in `bytes`, returns `None` if out of bounds.
+/// `T` must be valid for any bit pattern (plain integers and arrays of them).
+pub(crate) fn read_at<T: Copy>(bytes: &[u8], offset: usize) -> Option<T> {
+    if offset.checked_add(size_of::<T>())? > bytes.len() {
+        return None;
+    }
+    // Safety: Bounds checked above, T is only ever a plain-old-data type
+    Some(unsafe { read_unaligned(bytes.as_ptr().add(offset) as *const T) })
+}
+
+/// Copies as much of `bytes` as possible into a zeroed `T`. Used for tables which grew fields
+/// over revisions, where the missing fields read as 0.
+pub(crate) fn read_padded<T: Copy>(bytes: &[u8]) -> T {
+    let mut out = core::mem::MaybeUninit::<T>::zeroed();
+    let len = bytes.len().min(size_of::<T>());
+    // Safety: Copies at most `size_of::<T>()` bytes, T is only ever a plain-old-data type
+    unsafe {
+        ptr::copy_nonoverlapping(bytes.as_ptr(), out.as_mut_ptr() as *mut u8, len);
+        out.assume_init()
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct Rsdp {
+    pub signature: [u8; 8],
+    pub checksum: u8,
+    pub oem_id: [u8; 6],
+    pub revision: u8,
+    pub rsdt_address: u32,
+    // ACPI 2.0+
+    pub length: u32,
+    pub xsdt_address: u64,
+    pub extended_checksum: u8,
+    _reserved: [u8; 3],
+}
+
+impl Rsdp {
+    /// Creates an ACPI 2.0 RSDP pointing to `xsdt_address`, with valid checksums. ACPI 1.0
+    /// consumers only see `rsdt_address`, which may be 0 if there is no RSDT.
+    pub fn new(oem_id: [u8; 6], rsdt_address: u32, xsdt_address: u64) -> Self {
+        let mut rsdp = Self {
+            signature: *RSDP_SIGNATURE,
+            checksum: 0,
+            oem_id,
+            revision: 2,
+            rsdt_address,
+            length: size_of::<Rsdp>() as u32,
+            xsdt_address,
+            extended_checksum: 0,
+            _reserved: [0; 3],
+        };
+        let bytes = rsdp.as_bytes();
+        rsdp.checksum = checksum(&bytes[..RSDP_V1_SIZE]);
+        let bytes = rsdp.as_bytes();
+        rsdp.extended_checksum = checksum(&bytes);
+        rsdp
+    }
+
+    fn as_bytes(&self) -> [u8; size_of::<Rsdp>()] {
+        // Safety: The RSDP is packed plain-old-data, without padding
+        unsafe { core::mem::transmute(*self) }
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct SdtHeader {
+    pub signature: [u8; 4],
+    pub length: u32,
+    pub revision: u8,
+    pub checksum: u8,
+    pub oem_id: [u8; 6],
+    pub oem_table_id: [u8; 8],
+    pub oem_revision: u32,
+    pub creator_id: u32,
+    pub creator_revision: u32,
+}
+
+/// A system description table, as found in firmware memory.
+#[derive(Clone, Copy)]
+pub struct Sdt {
+    ptr: *const SdtHeader,
+}
+
+impl Sdt {
+    /// # Safety
+    /// `ptr` must point to a readable table, at least as long as its header states.
+    pub unsafe fn from_ptr(ptr: *const c_void) -> Self {
+        Self {
+            ptr: ptr as *const SdtHeader,
+        }
+    }
+
+    pub fn header(&self) -> SdtHeader {
+        // Safety: The header is always present, packed so any alignment is fine
+        unsafe { read_unaligned(self.ptr) }
+    }
+
+    pub fn signature(&self) -> [u8; 4] {
+        self.header().signature
+    }
+
+    pub fn address(&self) -> u64 {
+        self.ptr as u64
+    }
+
+    pub fn len(&self) -> usize {
+        self.header().length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() <= size_of::<SdtHeader>()
+    }
+
+    /// Whole table, header included
+    pub fn bytes(&self) -> &[u8] {
+        // Safety: The table is `length` bytes long
+        unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len()) }
+    }
+
+    /// Table content, after the header
+    pub fn data(&self) -> &[u8] {
+        self.bytes()
+            .get(size_of::<SdtHeader>()..)
+            .unwrap_or_default()
+    }
+
+    pub fn checksum_valid(&self) -> bool {
+        checksum_valid(self.bytes())
+    }
+
+    fn validate(&self) -> Result<(), AcpiError> {
+        if self.len() < size_of::<SdtHeader>() {
+            return Err(AcpiError::TableTooShort(self.signature()));
+        }
+        if !self.checksum_valid() {
+            return Err(AcpiError::InvalidChecksum(self.signature()));
+        }
+        Ok(())
+    }
+}
+
+/// Entry point of the ACPI tables, built from the RSDP found in the configuration table.
+pub struct Acpi {
+    rsdp: Rsdp,
+    /// XSDT if available, RSDT otherwise
+    root: Sdt,
+    /// Size of the entries of the root table (8 for the XSDT, 4 for the RSDT)
+    entry_size: usize,
+}
+
+impl Acpi {
+    /// Validates the RSDP and the root table (XSDT, or RSDT for ACPI 1.0) checksums.
+    ///
+    /// # Safety
+    /// `rsdp` must point to the RSDP, and every table it references must be identity-mapped.
+    pub unsafe fn from_rsdp(rsdp: *const c_void) -> Result<Self, AcpiError> {
+        let v1_bytes = unsafe { slice::from_raw_parts(rsdp as *const u8, RSDP_V1_SIZE) };
+        if &v1_bytes[..8] != RSDP_SIGNATURE {
+            return Err(AcpiError::InvalidRsdpSignature);
+        }
+        if !checksum_valid(v1_bytes) {
+            return Err(AcpiError::InvalidRsdpChecksum);
+        }
+
+        let revision = v1_bytes[15];
+        let rsdp: Rsdp = if revision >= 2 {
+            let copy = unsafe { read_unaligned(rsdp as *const Rsdp) };
+            let len = copy.length as usize;
+            if len < size_of::<Rsdp>() {
+                return Err(AcpiError::InvalidRsdpLength(copy.length));
+            }
+            // The extended checksum covers the whole structure
+            let bytes = unsafe { slice::from_raw_parts(rsdp as *const u8, len) };
+            if !checksum_valid(bytes) {
+                return Err(AcpiError::InvalidRsdpChecksum);
+            }
+            copy
+        } else {
+            read_padded(v1_bytes)
+        };
+
+        let (root, entry_size) = if revision >= 2 && rsdp.xsdt_address != 0 {
+            (rsdp.xsdt_address, size_of::<u64>())
+        } else if rsdp.rsdt_address != 0 {
+            (rsdp.rsdt_address as u64, size_of::<u32>())
+        } else {
+            return Err(AcpiError::NoRootTable);
+        };
+
+        let root = unsafe { Sdt::from_ptr(root as *const c_void) };
+        root.validate()?;
+
+        Ok(Self {
+            rsdp,
+            root,
+            entry_size,
+        })
+    }
+
+    pub fn rsdp(&self) -> &Rsdp {
+        &self.rsdp
+    }
+
+    pub fn revision(&self) -> u8 {
+        self.rsdp.revision
+    }
+
+    /// XSDT, or RSDT on ACPI 1.0 systems
+    pub fn root_table(&self) -> Sdt {
+        self.root
+    }
+
+    /// Iterates over every table referenced by the root table. Checksums aren't validated.
+    pub fn tables(&self) -> impl Iterator<Item = Sdt> + '_ {
+        self.root
+            .data()
+            .chunks_exact(self.entry_size)
+            .map(|entry| match self.entry_size {
+                4 => read_at::<u32>(entry, 0).unwrap_or(0) as u64,
+                _ => read_at::<u64>(entry, 0).unwrap_or(0),
+            })
+            .filter(|address| *address != 0)
+            // Safety: The root table only points to tables
+            .map(|address| unsafe { Sdt::from_ptr(address as *const c_void) })
+    }
+
+    /// Returns the first valid table with the given signature. Invalid duplicates are skipped,
+    /// the error of the first one is only returned if no table with this signature is valid.
+    pub fn find_table(&self, signature: &[u8; 4]) -> Result<Option<Sdt>, AcpiError> {
+        let mut error = None;
+        for table in self.tables().filter(|t| &t.signature() == signature) {
+            match table.validate() {
+                Ok(()) => return Ok(Some(table)),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(None),
+        }
+    }
+
+    pub fn madt(&self) -> Result<Option<Madt>, AcpiError> {
+        self.find_table(MADT_SIGNATURE)?.map(Madt::new).transpose()
+    }
+
+    pub fn fadt(&self) -> Result<Option<Fadt>, AcpiError> {
+        self.find_table(FADT_SIGNATURE)?.map(Fadt::new).transpose()
+    }
+
+    pub fn hpet(&self) -> Result<Option<Hpet>, AcpiError> {
+        self.find_table(HPET_SIGNATURE)?.map(Hpet::new).transpose()
+    }
+
+    /// DSDT, which is referenced by the FADT instead of the root table
+    pub fn dsdt(&self) -> Result<Option<Sdt>, AcpiError> {
+        let Some(fadt) = self.fadt()? else {
+            return Ok(None);
+        };
+        match fadt.dsdt_address() {
+            0 => Ok(None),
+            // Safety: The FADT points to the DSDT
+            address => Ok(Some(unsafe { Sdt::from_ptr(address as *const c_void) })),
+        }
+    }
+
+    pub fn mcfg(&self) -> Result<Option<Mcfg>, AcpiError> {
+        self.find_table(MCFG_SIGNATURE)?.map(Mcfg::new).transpose()
+    }
+}
+
+/// ACPI Generic Address Structure
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GenericAddress {
+    pub address_space_id: u8,
+    pub register_bit_width: u8,
+    pub register_bit_offset: u8,
+    pub access_size: u8,
+    pub address: u64,
+}
+
+impl GenericAddress {
+    pub const SYSTEM_MEMORY: u8 = 0;
+    pub const SYSTEM_IO: u8 = 1;
+    pub const PCI_CONFIGURATION: u8 = 2;
+
+    pub fn is_present(&self) -> bool {
+        let address = self.address;
+        address != 0
+    }
+}
+
+impl Display for GenericAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let address = self.address;
+        let space = match self.address_space_id {
+            Self::SYSTEM_MEMORY => "mem",
+            Self::SYSTEM_IO => "io",
+            Self::PCI_CONFIGURATION => "pci",
+            _ => "other",
+        };
+        write!(f, "{}:{:#x}", space, address)
+    }
+}
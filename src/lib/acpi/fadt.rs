@@ -0,0 +1,217 @@
+use core::mem::offset_of;
+
+use super::{fix_checksum, read_padded, AcpiError, GenericAddress, Sdt};
+
+pub const FADT_SIGNATURE: &[u8; 4] = b"FACP";
+
+/// Fields of the ACPI 1.0 FADT, the rest is optional
+const FADT_V1_SIZE: usize = 116;
+
+// FADT flags
+pub const FADT_RESET_REG_SUP: u32 = 1 << 10;
+pub const FADT_HW_REDUCED_ACPI: u32 = 1 << 20;
+
+// IA-PC boot architecture flags
+pub const FADT_LEGACY_DEVICES: u16 = 1 << 0;
+pub const FADT_8042: u16 = 1 << 1;
+pub const FADT_VGA_NOT_PRESENT: u16 = 1 << 2;
+pub const FADT_MSI_NOT_SUPPORTED: u16 = 1 << 3;
+pub const FADT_PCIE_ASPM_CONTROLS: u16 = 1 << 4;
+pub const FADT_CMOS_RTC_NOT_PRESENT: u16 = 1 << 5;
+
+/// Every FADT field up to ACPI 6.x, header included. Fields missing from older revisions are 0.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct RawFadt {
+    header: [u8; 36],
+    pub firmware_ctrl: u32,
+    pub dsdt: u32,
+    _reserved0: u8,
+    pub preferred_pm_profile: u8,
+    pub sci_int: u16,
+    pub smi_cmd: u32,
+    pub acpi_enable: u8,
+    pub acpi_disable: u8,
+    pub s4bios_req: u8,
+    pub pstate_cnt: u8,
+    pub pm1a_evt_blk: u32,
+    pub pm1b_evt_blk: u32,
+    pub pm1a_cnt_blk: u32,
+    pub pm1b_cnt_blk: u32,
+    pub pm2_cnt_blk: u32,
+    pub pm_tmr_blk: u32,
+    pub gpe0_blk: u32,
+    pub gpe1_blk: u32,
+    pub pm1_evt_len: u8,
+    pub pm1_cnt_len: u8,
+    pub pm2_cnt_len: u8,
+    pub pm_tmr_len: u8,
+    pub gpe0_blk_len: u8,
+    pub gpe1_blk_len: u8,
+    pub gpe1_base: u8,
+    pub cst_cnt: u8,
+    pub p_lvl2_lat: u16,
+    pub p_lvl3_lat: u16,
+    pub flush_size: u16,
+    pub flush_stride: u16,
+    pub duty_offset: u8,
+    pub duty_width: u8,
+    pub day_alrm: u8,
+    pub mon_alrm: u8,
+    pub century: u8,
+    pub iapc_boot_arch: u16,
+    _reserved1: u8,
+    pub flags: u32,
+    pub reset_reg: GenericAddress,
+    pub reset_value: u8,
+    pub arm_boot_arch: u16,
+    pub fadt_minor_version: u8,
+    pub x_firmware_ctrl: u64,
+    pub x_dsdt: u64,
+    pub x_pm1a_evt_blk: GenericAddress,
+    pub x_pm1b_evt_blk: GenericAddress,
+    pub x_pm1a_cnt_blk: GenericAddress,
+    pub x_pm1b_cnt_blk: GenericAddress,
+    pub x_pm2_cnt_blk: GenericAddress,
+    pub x_pm_tmr_blk: GenericAddress,
+    pub x_gpe0_blk: GenericAddress,
+    pub x_gpe1_blk: GenericAddress,
+    pub sleep_control_reg: GenericAddress,
+    pub sleep_status_reg: GenericAddress,
+    pub hypervisor_vendor_identity: u64,
+}
+
+/// Points a copy of a FADT (header included) to another DSDT, and fixes its checksum. ACPI 1.0
+/// FADTs only have a 32-bit field, so the DSDT must then be below 4 GiB.
+pub fn set_fadt_dsdt_address(fadt: &mut [u8], address: u64) -> Result<(), AcpiError> {
+    let dsdt = offset_of!(RawFadt, dsdt);
+    let x_dsdt = offset_of!(RawFadt, x_dsdt);
+    if fadt.len() < FADT_V1_SIZE {
+        return Err(AcpiError::TableTooShort(*FADT_SIGNATURE));
+    }
+
+    let has_x_dsdt = fadt.len() >= x_dsdt + 8;
+    // The 32-bit field must be 0 when the 64-bit one is used
+    let address_32 = if has_x_dsdt {
+        0
+    } else {
+        u32::try_from(address).map_err(|_| AcpiError::AddressTooHigh(*FADT_SIGNATURE))?
+    };
+    fadt[dsdt..dsdt + 4].copy_from_slice(&address_32.to_le_bytes());
+    if has_x_dsdt {
+        fadt[x_dsdt..x_dsdt + 8].copy_from_slice(&address.to_le_bytes());
+    }
+    fix_checksum(fadt);
+    Ok(())
+}
+
+/// Fixed ACPI Description Table
+#[derive(Clone, Copy)]
+pub struct Fadt {
+    table: Sdt,
+    raw: RawFadt,
+}
+
+impl Fadt {
+    pub(crate) fn new(table: Sdt) -> Result<Self, AcpiError> {
+        if table.len() < FADT_V1_SIZE {
+            return Err(AcpiError::TableTooShort(table.signature()));
+        }
+        Ok(Self {
+            table,
+            raw: read_padded(table.bytes()),
+        })
+    }
+
+    pub fn table(&self) -> Sdt {
+        self.table
+    }
+
+    pub fn raw(&self) -> &RawFadt {
+        &self.raw
+    }
+
+    /// DSDT address, preferring the 64-bit field
+    pub fn dsdt_address(&self) -> u64 {
+        match self.raw.x_dsdt {
+            0 => self.raw.dsdt as u64,
+            x => x,
+        }
+    }
+
+    /// FACS address, preferring the 64-bit field
+    pub fn facs_address(&self) -> u64 {
+        match self.raw.x_firmware_ctrl {
+            0 => self.raw.firmware_ctrl as u64,
+            x => x,
+        }
+    }
+
+    pub fn sci_interrupt(&self) -> u16 {
+        self.raw.sci_int
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.raw.flags
+    }
+
+    pub fn boot_arch_flags(&self) -> u16 {
+        self.raw.iapc_boot_arch
+    }
+
+    pub fn hardware_reduced(&self) -> bool {
+        self.flags() & FADT_HW_REDUCED_ACPI != 0
+    }
+
+    /// Register to write `reset_value` to in order to reset the system, if supported
+    pub fn reset_register(&self) -> Option<(GenericAddress, u8)> {
+        let reset_reg = self.raw.reset_reg;
+        if self.flags() & FADT_RESET_REG_SUP == 0 || !reset_reg.is_present() {
+            return None;
+        }
+        Some((reset_reg, self.raw.reset_value))
+    }
+
+    /// PM1a control block (used to enter sleep states), preferring the extended field
+    pub fn pm1a_control_block(&self) -> Option<GenericAddress> {
+        let x_block = self.raw.x_pm1a_cnt_blk;
+        if x_block.is_present() {
+            return Some(x_block);
+        }
+
+        match self.raw.pm1a_cnt_blk {
+            0 => None,
+            port => Some(GenericAddress {
+                address_space_id: GenericAddress::SYSTEM_IO,
+                register_bit_width: self.raw.pm1_cnt_len * 8,
+                register_bit_offset: 0,
+                access_size: 0,
+                address: port as u64,
+            }),
+        }
+    }
+
+    /// PM timer block, preferring the extended field
+    pub fn pm_timer_block(&self) -> Option<GenericAddress> {
+        let x_block = self.raw.x_pm_tmr_blk;
+        if x_block.is_present() {
+            return Some(x_block);
+        }
+
+        match self.raw.pm_tmr_blk {
+            0 => None,
+            port => Some(GenericAddress {
+                address_space_id: GenericAddress::SYSTEM_IO,
+                register_bit_width: 32,
+                register_bit_offset: 0,
+                access_size: 0,
+                address: port as u64,
+            }),
+        }
+    }
+
+    /// RTC CMOS index of the century, 0 if not supported
+    pub fn century_index(&self) -> u8 {
+        self.raw.century
+    }
+}
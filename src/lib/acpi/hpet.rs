@@ -0,0 +1,48 @@
+use super::{read_at, AcpiError, GenericAddress, Sdt};
+
+pub const HPET_SIGNATURE: &[u8; 4] = b"HPET";
+
+/// Size of the fixed HPET fields, after the SDT header
+const HPET_FIXED_SIZE: usize = 20;
+
+/// High Precision Event Timer table
+#[derive(Clone, Copy)]
+pub struct Hpet {
+    table: Sdt,
+}
+
+impl Hpet {
+    pub(crate) fn new(table: Sdt) -> Result<Self, AcpiError> {
+        if table.data().len() < HPET_FIXED_SIZE {
+            return Err(AcpiError::TableTooShort(table.signature()));
+        }
+        Ok(Self { table })
+    }
+
+    /// Hardware revision, comparator count and PCI vendor ID of the timer block
+    pub fn event_timer_block_id(&self) -> u32 {
+        read_at(self.table.data(), 0).unwrap_or(0)
+    }
+
+    pub fn pci_vendor_id(&self) -> u16 {
+        (self.event_timer_block_id() >> 16) as u16
+    }
+
+    /// Number of comparators in the first timer block
+    pub fn comparator_count(&self) -> u8 {
+        (((self.event_timer_block_id() >> 8) & 0x1F) + 1) as u8
+    }
+
+    pub fn base_address(&self) -> GenericAddress {
+        read_at(self.table.data(), 4).unwrap_or_default()
+    }
+
+    pub fn hpet_number(&self) -> u8 {
+        read_at(self.table.data(), 16).unwrap_or(0)
+    }
+
+    /// Minimum clock tick in periodic mode
+    pub fn minimum_tick(&self) -> u16 {
+        read_at(self.table.data(), 17).unwrap_or(0)
+    }
+}
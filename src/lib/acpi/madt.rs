@@ -0,0 +1,169 @@
+use super::{read_at, AcpiError, Sdt};
+
+pub const MADT_SIGNATURE: &[u8; 4] = b"APIC";
+
+/// Size of the fixed MADT fields, after the SDT header
+const MADT_FIXED_SIZE: usize = 8;
+
+/// Processor is usable right away
+pub const MADT_CPU_ENABLED: u32 = 1 << 0;
+/// Processor can be enabled at runtime
+pub const MADT_CPU_ONLINE_CAPABLE: u32 = 1 << 1;
+
+#[derive(Clone, Copy, Debug)]
+pub enum MadtEntry {
+    LocalApic {
+        processor_uid: u8,
+        apic_id: u8,
+        flags: u32,
+    },
+    IoApic {
+        id: u8,
+        address: u32,
+        gsi_base: u32,
+    },
+    InterruptSourceOverride {
+        bus: u8,
+        source: u8,
+        gsi: u32,
+        flags: u16,
+    },
+    NmiSource {
+        flags: u16,
+        gsi: u32,
+    },
+    LocalApicNmi {
+        processor_uid: u8,
+        flags: u16,
+        lint: u8,
+    },
+    LocalApicAddressOverride {
+        address: u64,
+    },
+    LocalX2Apic {
+        x2apic_id: u32,
+        flags: u32,
+        processor_uid: u32,
+    },
+    LocalX2ApicNmi {
+        processor_uid: u32,
+        flags: u16,
+        lint: u8,
+    },
+    Unknown {
+        entry_type: u8,
+        length: u8,
+    },
+}
+
+impl MadtEntry {
+    fn parse(entry_type: u8, e: &[u8]) -> Option<Self> {
+        let entry = match entry_type {
+            0 => MadtEntry::LocalApic {
+                processor_uid: read_at(e, 2)?,
+                apic_id: read_at(e, 3)?,
+                flags: read_at(e, 4)?,
+            },
+            1 => MadtEntry::IoApic {
+                id: read_at(e, 2)?,
+                address: read_at(e, 4)?,
+                gsi_base: read_at(e, 8)?,
+            },
+            2 => MadtEntry::InterruptSourceOverride {
+                bus: read_at(e, 2)?,
+                source: read_at(e, 3)?,
+                gsi: read_at(e, 4)?,
+                flags: read_at(e, 8)?,
+            },
+            3 => MadtEntry::NmiSource {
+                flags: read_at(e, 2)?,
+                gsi: read_at(e, 4)?,
+            },
+            4 => MadtEntry::LocalApicNmi {
+                processor_uid: read_at(e, 2)?,
+                flags: read_at(e, 3)?,
+                lint: read_at(e, 5)?,
+            },
+            5 => MadtEntry::LocalApicAddressOverride {
+                address: read_at(e, 4)?,
+            },
+            9 => MadtEntry::LocalX2Apic {
+                x2apic_id: read_at(e, 4)?,
+                flags: read_at(e, 8)?,
+                processor_uid: read_at(e, 12)?,
+            },
+            0xA => MadtEntry::LocalX2ApicNmi {
+                flags: read_at(e, 2)?,
+                processor_uid: read_at(e, 4)?,
+                lint: read_at(e, 8)?,
+            },
+            _ => MadtEntry::Unknown {
+                entry_type,
+                length: e.len() as u8,
+            },
+        };
+        Some(entry)
+    }
+}
+
+/// Multiple APIC Description Table
+#[derive(Clone, Copy)]
+pub struct Madt {
+    table: Sdt,
+}
+
+impl Madt {
+    pub(crate) fn new(table: Sdt) -> Result<Self, AcpiError> {
+        if table.data().len() < MADT_FIXED_SIZE {
+            return Err(AcpiError::TableTooShort(table.signature()));
+        }
+        Ok(Self { table })
+    }
+
+    /// 32-bit local APIC address, see `local_apic_address` for the effective one.
+    pub fn local_apic_address_32(&self) -> u32 {
+        read_at(self.table.data(), 0).unwrap_or(0)
+    }
+
+    /// Local APIC address, taking the address override entry into account
+    pub fn local_apic_address(&self) -> u64 {
+        self.entries()
+            .find_map(|e| match e {
+                MadtEntry::LocalApicAddressOverride { address } => Some(address),
+                _ => None,
+            })
+            .unwrap_or(self.local_apic_address_32() as u64)
+    }
+
+    /// Whether the system also has dual 8259 PICs
+    pub fn pcat_compat(&self) -> bool {
+        read_at::<u32>(self.table.data(), 4).unwrap_or(0) & 1 != 0
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = MadtEntry> + '_ {
+        let mut remaining = &self.table.data()[MADT_FIXED_SIZE..];
+        core::iter::from_fn(move || {
+            let entry_type = *remaining.first()?;
+            let length = *remaining.get(1)? as usize;
+            if length < 2 || length > remaining.len() {
+                return None;
+            }
+            let (entry, rest) = remaining.split_at(length);
+            remaining = rest;
+            MadtEntry::parse(entry_type, entry)
+        })
+    }
+
+    /// Number of usable (enabled or online capable) processors
+    pub fn processor_count(&self) -> usize {
+        let usable = MADT_CPU_ENABLED | MADT_CPU_ONLINE_CAPABLE;
+        self.entries()
+            .filter(|e| match e {
+                MadtEntry::LocalApic { flags, .. } | MadtEntry::LocalX2Apic { flags, .. } => {
+                    flags & usable != 0
+                }
+                _ => false,
+            })
+            .count()
+    }
+}
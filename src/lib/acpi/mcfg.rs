@@ -0,0 +1,42 @@
+use super::{read_at, AcpiError, Sdt};
+
+pub const MCFG_SIGNATURE: &[u8; 4] = b"MCFG";
+
+/// Reserved bytes before the first entry, after the SDT header
+const MCFG_FIXED_SIZE: usize = 8;
+const MCFG_ENTRY_SIZE: usize = 16;
+
+/// PCI Express enhanced configuration space of a segment group
+#[derive(Clone, Copy, Debug)]
+pub struct McfgEntry {
+    pub base_address: u64,
+    pub segment_group: u16,
+    pub start_bus: u8,
+    pub end_bus: u8,
+}
+
+/// PCI Express memory mapped configuration space table
+#[derive(Clone, Copy)]
+pub struct Mcfg {
+    table: Sdt,
+}
+
+impl Mcfg {
+    pub(crate) fn new(table: Sdt) -> Result<Self, AcpiError> {
+        if table.data().len() < MCFG_FIXED_SIZE {
+            return Err(AcpiError::TableTooShort(table.signature()));
+        }
+        Ok(Self { table })
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = McfgEntry> + '_ {
+        self.table.data()[MCFG_FIXED_SIZE..]
+            .chunks_exact(MCFG_ENTRY_SIZE)
+            .map(|e| McfgEntry {
+                base_address: read_at(e, 0).unwrap_or(0),
+                segment_group: read_at(e, 8).unwrap_or(0),
+                start_bus: read_at(e, 10).unwrap_or(0),
+                end_bus: read_at(e, 11).unwrap_or(0),
+            })
+    }
+}
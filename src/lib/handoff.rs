@@ -0,0 +1,198 @@
+use bitflags::bitflags;
+
+/// "PUBBOOT\0" in little-endian
+pub const BOOT_INFO_MAGIC: u64 = u64::from_le_bytes(*b"PUBBOOT\0");
+pub const BOOT_INFO_REVISION: u32 = 3;
+
+pub const ACPI_INFO_MAX_CPUS: usize = 256;
+pub const ACPI_INFO_MAX_IO_APICS: usize = 16;
+pub const ACPI_INFO_MAX_OVERRIDES: usize = 16;
+pub const ACPI_INFO_MAX_PCI_SEGMENTS: usize = 16;
+
+bitflags! {
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+    pub struct BootInfoFlags: u32 {
+        /// Boot services were exited before jumping to the kernel, the memory map is final.
+        const BootServicesExited = 0x00000001;
+        /// PUB called `SetVirtualAddressMap`, runtime services must be called through the
+        /// virtual addresses found in the memory map.
+        const VirtualMapSet      = 0x00000002;
+        const _                  = !0;
+    }
+}
+
+/// Handed to the kernel by PUB, a pointer to this structure is passed as the first argument
+/// (System V ABI) of the kernel entrypoint. Only uses fixed-size fields, so kernels don't need to
+/// depend on this crate to read it.
+///
+/// Addresses are physical, unless stated otherwise. An address of 0 means the information isn't
+/// available.
+#[repr(C)]
+#[derive(Debug)]
+pub struct BootInfo {
+    pub magic: u64,
+    pub revision: u32,
+    pub flags: BootInfoFlags,
+
+    /// UEFI memory map, made of `EFI_MEMORY_DESCRIPTOR`s. The `virtual_start` field of runtime
+    /// descriptors contains the mapping chosen by PUB.
+    pub memory_map: u64,
+    pub memory_map_size: u64,
+    pub memory_descriptor_size: u64,
+    pub memory_descriptor_version: u32,
+    _reserved: u32,
+
+    /// `EFI_SYSTEM_TABLE`, virtual if `VirtualMapSet` is set
+    pub system_table: u64,
+    /// `EFI_RUNTIME_SERVICES`, virtual if `VirtualMapSet` is set
+    pub runtime_services: u64,
+    /// `EFI_MEMORY_ATTRIBUTES_TABLE`
+    pub memory_attributes_table: u64,
+
+    // Revision 2
+    /// ACPI RSDP, revision 2+ if available
+    pub acpi_rsdp: u64,
+    /// SMBIOS 2.x entry point
+    pub smbios: u64,
+    /// SMBIOS 3.x entry point
+    pub smbios3: u64,
+    /// Flattened device tree blob
+    pub device_tree: u64,
+
+    // Revision 3
+    /// `AcpiInfo`, only passed if the boot entry asks for it
+    pub acpi_info: u64,
+}
+
+impl Default for BootInfo {
+    fn default() -> Self {
+        Self {
+            magic: BOOT_INFO_MAGIC,
+            revision: BOOT_INFO_REVISION,
+            flags: BootInfoFlags::empty(),
+            memory_map: 0,
+            memory_map_size: 0,
+            memory_descriptor_size: 0,
+            memory_descriptor_version: 0,
+            _reserved: 0,
+            system_table: 0,
+            runtime_services: 0,
+            memory_attributes_table: 0,
+            acpi_rsdp: 0,
+            smbios: 0,
+            smbios3: 0,
+            device_tree: 0,
+            acpi_info: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcpiCpu {
+    pub processor_uid: u32,
+    /// Local APIC or x2APIC ID
+    pub apic_id: u32,
+    /// MADT flags (bit 0: enabled, bit 1: online capable)
+    pub flags: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcpiIoApic {
+    pub id: u32,
+    pub address: u32,
+    pub gsi_base: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcpiInterruptOverride {
+    pub bus: u8,
+    pub source: u8,
+    /// MPS INTI flags (polarity and trigger mode)
+    pub flags: u16,
+    pub gsi: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcpiPciSegment {
+    pub base_address: u64,
+    pub segment_group: u16,
+    pub start_bus: u8,
+    pub end_bus: u8,
+    _reserved: u32,
+}
+
+impl AcpiPciSegment {
+    pub fn new(base_address: u64, segment_group: u16, start_bus: u8, end_bus: u8) -> Self {
+        Self {
+            base_address,
+            segment_group,
+            start_bus,
+            end_bus,
+            _reserved: 0,
+        }
+    }
+}
+
+/// ACPI information decoded by PUB (MADT, FADT, HPET and MCFG), so kernels can bring up
+/// interrupts and timers without an ACPI parser. Entries past the counts are zeroed.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct AcpiInfo {
+    pub revision: u8,
+    _reserved: [u8; 7],
+    pub local_apic_address: u64,
+    /// Whether dual 8259 PICs are present and need to be masked
+    pub pcat_compat: u8,
+    _reserved1: [u8; 3],
+    pub cpu_count: u32,
+    pub io_apic_count: u32,
+    pub override_count: u32,
+    pub pci_segment_count: u32,
+    /// FADT SCI interrupt
+    pub sci_interrupt: u16,
+    /// FADT IA-PC boot architecture flags
+    pub boot_arch_flags: u16,
+    /// FADT flags
+    pub fadt_flags: u32,
+    _reserved2: u32,
+    pub dsdt: u64,
+    pub facs: u64,
+    /// HPET base address (memory space), 0 if there is no HPET
+    pub hpet_address: u64,
+    pub cpus: [AcpiCpu; ACPI_INFO_MAX_CPUS],
+    pub io_apics: [AcpiIoApic; ACPI_INFO_MAX_IO_APICS],
+    pub overrides: [AcpiInterruptOverride; ACPI_INFO_MAX_OVERRIDES],
+    pub pci_segments: [AcpiPciSegment; ACPI_INFO_MAX_PCI_SEGMENTS],
+}
+
+impl Default for AcpiInfo {
+    fn default() -> Self {
+        Self {
+            revision: 0,
+            _reserved: [0; 7],
+            local_apic_address: 0,
+            pcat_compat: 0,
+            _reserved1: [0; 3],
+            cpu_count: 0,
+            io_apic_count: 0,
+            override_count: 0,
+            pci_segment_count: 0,
+            sci_interrupt: 0,
+            boot_arch_flags: 0,
+            fadt_flags: 0,
+            _reserved2: 0,
+            dsdt: 0,
+            facs: 0,
+            hpet_address: 0,
+            cpus: [AcpiCpu::default(); ACPI_INFO_MAX_CPUS],
+            io_apics: [AcpiIoApic::default(); ACPI_INFO_MAX_IO_APICS],
+            overrides: [AcpiInterruptOverride::default(); ACPI_INFO_MAX_OVERRIDES],
+            pci_segments: [AcpiPciSegment::default(); ACPI_INFO_MAX_PCI_SEGMENTS],
+        }
+    }
+}
@@ -1,5 +1,8 @@
 #![no_std]
 
+pub mod acpi;
 pub mod elf;
+pub mod handoff;
 pub mod macros;
+pub mod smbios;
 pub mod uefi;
@@ -43,3 +43,19 @@ macro_rules! println {
         $crate::uefi::helper::_print(core::format_args!($($arg)*), stdout, true);
     }};
 }
+
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => {{
+        let stderr = unsafe { $crate::uefi::helper::_get_st_panicking().stderr() };
+        $crate::uefi::helper::_print(core::format_args!($($arg)*), stderr, false);
+    }};
+}
+
+#[macro_export]
+macro_rules! eprintln {
+    ($($arg:tt)*) => {{
+        let stderr = unsafe { $crate::uefi::helper::_get_st_panicking().stderr() };
+        $crate::uefi::helper::_print(core::format_args!($($arg)*), stderr, true);
+    }};
+}
@@ -0,0 +1,242 @@
+mod baseboard;
+mod bios;
+mod memory;
+mod processor;
+mod system;
+
+use core::{ffi::c_void, fmt::Display, slice};
+
+pub use baseboard::*;
+pub use bios::*;
+pub use memory::*;
+pub use processor::*;
+pub use system::*;
+
+use crate::{acpi::read_at, uefi::Guid};
+
+const ENTRY_POINT_ANCHOR: &[u8; 4] = b"_SM_";
+const INTERMEDIATE_ANCHOR: &[u8; 5] = b"_DMI_";
+const ENTRY_POINT3_ANCHOR: &[u8; 5] = b"_SM3_";
+/// Offset of the intermediate entry point in the 2.x entry point, covered by its own checksum
+const INTERMEDIATE_OFFSET: usize = 0x10;
+const INTERMEDIATE_SIZE: usize = 0xF;
+const ENTRY_POINT_SIZE: usize = 0x1F;
+const ENTRY_POINT3_SIZE: usize = 0x18;
+
+/// Size of the header every structure starts with
+const STRUCTURE_HEADER_SIZE: usize = 4;
+const END_OF_TABLE: u8 = 127;
+
+#[derive(Debug, Clone, Copy)]
+pub enum SmbiosError {
+    InvalidAnchor,
+    InvalidChecksum,
+    /// Entry point length is smaller than the structure it describes
+    InvalidLength,
+}
+
+impl Display for SmbiosError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SmbiosError::InvalidAnchor => write!(f, "invalid SMBIOS entry point anchor"),
+            SmbiosError::InvalidChecksum => write!(f, "invalid SMBIOS entry point checksum"),
+            SmbiosError::InvalidLength => write!(f, "invalid SMBIOS entry point length"),
+        }
+    }
+}
+
+fn checksum_valid(bytes: &[u8]) -> bool {
+    bytes.iter().fold(0_u8, |acc, b| acc.wrapping_add(*b)) == 0
+}
+
+/// Structure table, found through the SMBIOS 2.x or 3.x entry point.
+pub struct Smbios {
+    major: u8,
+    minor: u8,
+    table: *const u8,
+    /// Exact length for 2.x, maximum length for 3.x (the table ends with an end-of-table
+    /// structure)
+    table_len: usize,
+}
+
+impl Smbios {
+    /// Validates the SMBIOS 2.x (32-bit) entry point and its intermediate checksum.
+    ///
+    /// # Safety
+    /// `entry_point` must point to the entry point, and the structure table must be
+    /// identity-mapped.
+    pub unsafe fn from_entry_point(entry_point: *const c_void) -> Result<Self, SmbiosError> {
+        let bytes = unsafe { slice::from_raw_parts(entry_point as *const u8, ENTRY_POINT_SIZE) };
+        if &bytes[..4] != ENTRY_POINT_ANCHOR
+            || &bytes[INTERMEDIATE_OFFSET..INTERMEDIATE_OFFSET + 5] != INTERMEDIATE_ANCHOR
+        {
+            return Err(SmbiosError::InvalidAnchor);
+        }
+
+        // Some firmware reports 0x1E (a mistake in SMBIOS 2.1), which is still checksummed
+        let len = bytes[5] as usize;
+        if len < ENTRY_POINT_SIZE - 1 {
+            return Err(SmbiosError::InvalidLength);
+        }
+        let checksummed = unsafe { slice::from_raw_parts(entry_point as *const u8, len) };
+        let intermediate = &bytes[INTERMEDIATE_OFFSET..INTERMEDIATE_OFFSET + INTERMEDIATE_SIZE];
+        if !checksum_valid(checksummed) || !checksum_valid(intermediate) {
+            return Err(SmbiosError::InvalidChecksum);
+        }
+
+        Ok(Self {
+            major: bytes[6],
+            minor: bytes[7],
+            table: read_at::<u32>(bytes, 0x18).unwrap_or(0) as usize as *const u8,
+            table_len: read_at::<u16>(bytes, 0x16).unwrap_or(0) as usize,
+        })
+    }
+
+    /// Validates the SMBIOS 3.x (64-bit) entry point.
+    ///
+    /// # Safety
+    /// `entry_point` must point to the entry point, and the structure table must be
+    /// identity-mapped.
+    pub unsafe fn from_entry_point3(entry_point: *const c_void) -> Result<Self, SmbiosError> {
+        let bytes = unsafe { slice::from_raw_parts(entry_point as *const u8, ENTRY_POINT3_SIZE) };
+        if &bytes[..5] != ENTRY_POINT3_ANCHOR {
+            return Err(SmbiosError::InvalidAnchor);
+        }
+
+        let len = bytes[6] as usize;
+        if len < ENTRY_POINT3_SIZE {
+            return Err(SmbiosError::InvalidLength);
+        }
+        let checksummed = unsafe { slice::from_raw_parts(entry_point as *const u8, len) };
+        if !checksum_valid(checksummed) {
+            return Err(SmbiosError::InvalidChecksum);
+        }
+
+        Ok(Self {
+            major: bytes[7],
+            minor: bytes[8],
+            table: read_at::<u64>(bytes, 0x10).unwrap_or(0) as usize as *const u8,
+            table_len: read_at::<u32>(bytes, 0xC).unwrap_or(0) as usize,
+        })
+    }
+
+    /// SMBIOS version, as (major, minor)
+    pub fn version(&self) -> (u8, u8) {
+        (self.major, self.minor)
+    }
+
+    pub fn table_address(&self) -> u64 {
+        self.table as u64
+    }
+
+    /// Iterates over every structure, up to the end-of-table structure.
+    pub fn structures(&self) -> impl Iterator<Item = Structure<'_>> + '_ {
+        let mut remaining: &[u8] = if self.table.is_null() {
+            &[]
+        } else {
+            // Safety: The entry point states the table is (at most) `table_len` bytes long
+            unsafe { slice::from_raw_parts(self.table, self.table_len) }
+        };
+
+        core::iter::from_fn(move || {
+            let structure = Structure::parse(remaining)?;
+            remaining = &remaining[structure.size()..];
+            Some(structure)
+        })
+        .take_while(|s| s.structure_type != END_OF_TABLE)
+    }
+
+    pub fn structures_of_type(&self, structure_type: u8) -> impl Iterator<Item = Structure<'_>> {
+        self.structures()
+            .filter(move |s| s.structure_type == structure_type)
+    }
+
+    pub fn bios(&self) -> Option<BiosInfo<'_>> {
+        self.structures_of_type(BIOS_INFO_TYPE)
+            .next()
+            .map(BiosInfo::new)
+    }
+
+    pub fn system(&self) -> Option<SystemInfo<'_>> {
+        self.structures_of_type(SYSTEM_INFO_TYPE)
+            .next()
+            .map(SystemInfo::new)
+    }
+
+    pub fn baseboard(&self) -> Option<BaseboardInfo<'_>> {
+        self.structures_of_type(BASEBOARD_INFO_TYPE)
+            .next()
+            .map(BaseboardInfo::new)
+    }
+
+    pub fn processors(&self) -> impl Iterator<Item = ProcessorInfo<'_>> {
+        self.structures_of_type(PROCESSOR_INFO_TYPE)
+            .map(ProcessorInfo::new)
+    }
+
+    pub fn memory_devices(&self) -> impl Iterator<Item = MemoryDevice<'_>> {
+        self.structures_of_type(MEMORY_DEVICE_TYPE)
+            .map(MemoryDevice::new)
+    }
+}
+
+/// A structure of the SMBIOS table: a formatted area followed by its strings.
+#[derive(Clone, Copy)]
+pub struct Structure<'a> {
+    pub structure_type: u8,
+    pub handle: u16,
+    /// Formatted area, header included
+    formatted: &'a [u8],
+    /// String set, made of null-terminated strings and ending with an extra null byte
+    strings: &'a [u8],
+}
+
+impl<'a> Structure<'a> {
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let length = *bytes.get(1)? as usize;
+        if length < STRUCTURE_HEADER_SIZE || length > bytes.len() {
+            return None;
+        }
+        let (formatted, rest) = bytes.split_at(length);
+
+        // The string set ends with a double null, even when there is no string
+        let strings_len = rest.windows(2).position(|w| w == [0, 0])?;
+        Some(Self {
+            structure_type: formatted[0],
+            handle: read_at(formatted, 2)?,
+            formatted,
+            strings: &rest[..strings_len + 2],
+        })
+    }
+
+    /// Size of the whole structure, strings included
+    fn size(&self) -> usize {
+        self.formatted.len() + self.strings.len()
+    }
+
+    /// Formatted area, header included
+    pub fn formatted(&self) -> &'a [u8] {
+        self.formatted
+    }
+
+    /// Reads a field of the formatted area, `None` if the structure is too short to have it
+    /// (i.e. it was added by a later SMBIOS version).
+    pub fn read<T: Copy>(&self, offset: usize) -> Option<T> {
+        read_at(self.formatted, offset)
+    }
+
+    /// Returns the string referenced by the byte field at `offset`. Strings are numbered from
+    /// 1, 0 meaning no string.
+    pub fn string(&self, offset: usize) -> Option<&'a str> {
+        let index = self.read::<u8>(offset)? as usize;
+        let bytes = self
+            .strings
+            .split(|b| *b == 0)
+            .nth(index.checked_sub(1)?)
+            .filter(|s| !s.is_empty())?;
+        core::str::from_utf8(bytes).ok().map(str::trim)
+    }
+}
+
+/// A UUID as stored by SMBIOS 2.6+: the first three fields are little-endian, like a `Guid`.
+pub type Uuid = Guid;
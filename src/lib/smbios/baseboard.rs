@@ -0,0 +1,39 @@
+use super::Structure;
+
+pub const BASEBOARD_INFO_TYPE: u8 = 2;
+
+/// Baseboard (or Module) Information (type 2)
+#[derive(Clone, Copy)]
+pub struct BaseboardInfo<'a> {
+    structure: Structure<'a>,
+}
+
+impl<'a> BaseboardInfo<'a> {
+    pub(crate) fn new(structure: Structure<'a>) -> Self {
+        Self { structure }
+    }
+
+    pub fn structure(&self) -> Structure<'a> {
+        self.structure
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string(0x4)
+    }
+
+    pub fn product(&self) -> Option<&'a str> {
+        self.structure.string(0x5)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string(0x6)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.structure.string(0x7)
+    }
+
+    pub fn asset_tag(&self) -> Option<&'a str> {
+        self.structure.string(0x8)
+    }
+}
@@ -0,0 +1,64 @@
+use super::Structure;
+
+pub const BIOS_INFO_TYPE: u8 = 0;
+
+/// BIOS Information (type 0)
+#[derive(Clone, Copy)]
+pub struct BiosInfo<'a> {
+    structure: Structure<'a>,
+}
+
+impl<'a> BiosInfo<'a> {
+    pub(crate) fn new(structure: Structure<'a>) -> Self {
+        Self { structure }
+    }
+
+    pub fn structure(&self) -> Structure<'a> {
+        self.structure
+    }
+
+    pub fn vendor(&self) -> Option<&'a str> {
+        self.structure.string(0x4)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string(0x5)
+    }
+
+    pub fn release_date(&self) -> Option<&'a str> {
+        self.structure.string(0x8)
+    }
+
+    /// BIOS (major, minor) release, SMBIOS 2.4+. Not available if both are 0xFF.
+    pub fn release(&self) -> Option<(u8, u8)> {
+        let major = self.structure.read(0x14)?;
+        let minor = self.structure.read(0x15)?;
+        match (major, minor) {
+            (0xFF, 0xFF) => None,
+            release => Some(release),
+        }
+    }
+
+    /// Size of the BIOS ROM in bytes, using the extended size field (SMBIOS 3.1+) if needed
+    pub fn rom_size(&self) -> Option<u64> {
+        match self.structure.read::<u8>(0x9)? {
+            0xFF => {
+                let extended = self.structure.read::<u16>(0x18)?;
+                let size = (extended & 0x3FFF) as u64;
+                // Bits 14-15 give the unit: MiB or GiB
+                match extended >> 14 {
+                    0 => Some(size << 20),
+                    1 => Some(size << 30),
+                    _ => None,
+                }
+            }
+            // In 64 KiB units, minus one
+            size => Some((size as u64 + 1) << 16),
+        }
+    }
+
+    /// BIOS characteristics bitfield
+    pub fn characteristics(&self) -> u64 {
+        self.structure.read(0xA).unwrap_or(0)
+    }
+}
@@ -0,0 +1,99 @@
+use super::Structure;
+
+pub const MEMORY_DEVICE_TYPE: u8 = 17;
+
+/// Size field value meaning the size is in the extended size field
+const EXTENDED_SIZE: u16 = 0x7FFF;
+const UNKNOWN_SIZE: u16 = 0xFFFF;
+/// Set in the size field when the size is in KiB rather than MiB
+const SIZE_IN_KIB: u16 = 1 << 15;
+
+/// Memory Device (type 17), usually a DIMM slot
+#[derive(Clone, Copy)]
+pub struct MemoryDevice<'a> {
+    structure: Structure<'a>,
+}
+
+impl<'a> MemoryDevice<'a> {
+    pub(crate) fn new(structure: Structure<'a>) -> Self {
+        Self { structure }
+    }
+
+    pub fn structure(&self) -> Structure<'a> {
+        self.structure
+    }
+
+    /// Size in bytes, `Some(0)` if the slot is empty and `None` if unknown
+    pub fn size(&self) -> Option<u64> {
+        match self.structure.read::<u16>(0xC)? {
+            UNKNOWN_SIZE => None,
+            EXTENDED_SIZE => {
+                let size = self.structure.read::<u32>(0x1C)? & 0x7FFF_FFFF;
+                Some((size as u64) << 20)
+            }
+            size if size & SIZE_IN_KIB != 0 => Some(((size & !SIZE_IN_KIB) as u64) << 10),
+            size => Some((size as u64) << 20),
+        }
+    }
+
+    pub fn is_installed(&self) -> bool {
+        self.size() != Some(0)
+    }
+
+    /// Socket or board position, e.g. "DIMM 0"
+    pub fn device_locator(&self) -> Option<&'a str> {
+        self.structure.string(0x10)
+    }
+
+    pub fn bank_locator(&self) -> Option<&'a str> {
+        self.structure.string(0x11)
+    }
+
+    pub fn memory_type(&self) -> u8 {
+        self.structure.read(0x12).unwrap_or(0)
+    }
+
+    /// Name of the memory type (DDR4, LPDDR5...)
+    pub fn memory_type_name(&self) -> &'static str {
+        match self.memory_type() {
+            0x03 => "DRAM",
+            0x07 => "RAM",
+            0x0F => "SDRAM",
+            0x12 => "DDR",
+            0x13 => "DDR2",
+            0x18 => "DDR3",
+            0x1A => "DDR4",
+            0x1B => "LPDDR",
+            0x1C => "LPDDR2",
+            0x1D => "LPDDR3",
+            0x1E => "LPDDR4",
+            0x20 => "HBM",
+            0x21 => "HBM2",
+            0x22 => "DDR5",
+            0x23 => "LPDDR5",
+            _ => "Unknown",
+        }
+    }
+
+    /// Maximum speed in MT/s (SMBIOS 2.3+)
+    pub fn speed(&self) -> Option<u16> {
+        self.structure.read(0x15).filter(|s| *s != 0)
+    }
+
+    /// Configured speed in MT/s (SMBIOS 2.7+)
+    pub fn configured_speed(&self) -> Option<u16> {
+        self.structure.read(0x20).filter(|s| *s != 0)
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string(0x17)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.structure.string(0x18)
+    }
+
+    pub fn part_number(&self) -> Option<&'a str> {
+        self.structure.string(0x1A)
+    }
+}
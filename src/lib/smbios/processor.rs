@@ -0,0 +1,84 @@
+use super::Structure;
+
+pub const PROCESSOR_INFO_TYPE: u8 = 4;
+
+/// Socket is populated (bit 6 of the status field)
+const STATUS_POPULATED: u8 = 1 << 6;
+
+/// Processor Information (type 4)
+#[derive(Clone, Copy)]
+pub struct ProcessorInfo<'a> {
+    structure: Structure<'a>,
+}
+
+impl<'a> ProcessorInfo<'a> {
+    pub(crate) fn new(structure: Structure<'a>) -> Self {
+        Self { structure }
+    }
+
+    pub fn structure(&self) -> Structure<'a> {
+        self.structure
+    }
+
+    pub fn socket(&self) -> Option<&'a str> {
+        self.structure.string(0x4)
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string(0x7)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string(0x10)
+    }
+
+    /// Raw processor ID (CPUID leaf 1 EAX and EDX on x86)
+    pub fn id(&self) -> u64 {
+        self.structure.read(0x8).unwrap_or(0)
+    }
+
+    pub fn is_populated(&self) -> bool {
+        self.structure.read::<u8>(0x18).unwrap_or(0) & STATUS_POPULATED != 0
+    }
+
+    /// External clock in MHz
+    pub fn external_clock(&self) -> Option<u16> {
+        self.structure.read(0x12).filter(|s| *s != 0)
+    }
+
+    /// Maximum speed in MHz
+    pub fn max_speed(&self) -> Option<u16> {
+        self.structure.read(0x14).filter(|s| *s != 0)
+    }
+
+    /// Speed at boot in MHz
+    pub fn current_speed(&self) -> Option<u16> {
+        self.structure.read(0x16).filter(|s| *s != 0)
+    }
+
+    /// Number of cores (SMBIOS 2.5+), using the 16-bit field (SMBIOS 3.0+) if needed
+    pub fn core_count(&self) -> Option<u16> {
+        self.count(0x23, 0x2A)
+    }
+
+    pub fn cores_enabled(&self) -> Option<u16> {
+        self.count(0x24, 0x2C)
+    }
+
+    pub fn thread_count(&self) -> Option<u16> {
+        self.count(0x25, 0x2E)
+    }
+
+    /// Counts are 0 when unknown, and 0xFF when the 16-bit field holds the real value
+    fn count(&self, offset: usize, offset_16: usize) -> Option<u16> {
+        match self.structure.read::<u8>(offset)? {
+            0 => None,
+            0xFF => self
+                .structure
+                .read(offset_16)
+                .filter(|c| *c != 0)
+                .or(Some(0xFF)),
+            count => Some(count as u16),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use super::{Structure, Uuid};
+
+pub const SYSTEM_INFO_TYPE: u8 = 1;
+
+/// System Information (type 1)
+#[derive(Clone, Copy)]
+pub struct SystemInfo<'a> {
+    structure: Structure<'a>,
+}
+
+impl<'a> SystemInfo<'a> {
+    pub(crate) fn new(structure: Structure<'a>) -> Self {
+        Self { structure }
+    }
+
+    pub fn structure(&self) -> Structure<'a> {
+        self.structure
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string(0x4)
+    }
+
+    pub fn product_name(&self) -> Option<&'a str> {
+        self.structure.string(0x5)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string(0x6)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.structure.string(0x7)
+    }
+
+    /// System UUID (SMBIOS 2.1+), `None` if the firmware doesn't provide one
+    pub fn uuid(&self) -> Option<Uuid> {
+        // All zeroes or all ones mean the UUID isn't available
+        self.structure
+            .read(0x8)
+            .filter(|b: &[u8; 16]| *b != [0; 16] && *b != [0xFF; 16])
+            .map(Uuid::from_bytes)
+    }
+
+    /// SMBIOS 2.4+
+    pub fn sku_number(&self) -> Option<&'a str> {
+        self.structure.string(0x19)
+    }
+
+    /// SMBIOS 2.4+
+    pub fn family(&self) -> Option<&'a str> {
+        self.structure.string(0x1A)
+    }
+}
@@ -1,13 +1,26 @@
 pub mod boot_services;
+pub mod config_table;
+pub mod device_path;
+pub mod event;
+pub mod guid;
 pub mod helper;
+pub mod memory_map;
 pub mod protocols;
+pub mod runtime_services;
 pub mod status;
 pub mod string;
+pub mod sync;
 
-use core::{ffi::c_void, ptr::NonNull};
+use core::{ffi::c_void, fmt::Display, ptr::NonNull, slice};
 
 use boot_services::{BootServices, RawBootServices};
-use protocols::Output;
+use config_table::ConfigurationTable;
+pub use guid::Guid;
+use helper::AllocatedPool;
+use protocols::{Input, Output};
+use runtime_services::{RawRuntimeServices, RuntimeServices};
+use status::StatusError;
+use string::CStr16;
 
 #[repr(C)]
 pub enum MemoryType {
@@ -39,28 +52,42 @@ pub enum AllocateType {
 }
 
 pub type PhysicalAddress = u64;
+pub type VirtualAddress = u64;
 
 #[repr(transparent)]
-pub struct Guid([u8; 16]);
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Handle(NonNull<*mut c_void>);
 
-impl Guid {
-    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
-        Self(bytes)
+impl Handle {
+    pub fn from_ptr(ptr: *mut *mut c_void) -> Option<Self> {
+        NonNull::new(ptr).map(Self)
     }
-}
 
-#[repr(transparent)]
-#[derive(Clone, Copy)]
-pub struct Handle(NonNull<*mut c_void>);
+    pub fn as_ptr(&self) -> *mut *mut c_void {
+        self.0.as_ptr()
+    }
+}
 
 #[repr(transparent)]
 pub struct SystemTable(*const RawSystemTable);
 
 impl SystemTable {
+    /// `IBI SYST`
+    pub const SIGNATURE: u64 = 0x5453_5953_2049_4249;
+
     pub fn stdout(&mut self) -> &mut Output {
         unsafe { &mut *(*self.0).con_out }
     }
 
+    /// Console meant for errors, which may be the same device as `stdout`
+    pub fn stderr(&mut self) -> &mut Output {
+        unsafe { &mut *(*self.0).std_err }
+    }
+
+    pub fn stdin(&mut self) -> &mut Input {
+        unsafe { &mut *(*self.0).con_in }
+    }
+
     pub fn boot_services(&mut self) -> BootServices {
         unsafe {
             let x = &*self.0;
@@ -68,6 +95,72 @@ impl SystemTable {
             BootServices::from_ptr(x)
         }
     }
+
+    pub fn runtime_services(&self) -> RuntimeServices {
+        // Safety: The runtime services table stays valid for the lifetime of the system table
+        unsafe { RuntimeServices::from_ptr((*self.0).runtime_services) }
+    }
+
+    /// Iterates over the configuration table, as (vendor GUID, table) pairs.
+    pub fn config_tables(&self) -> impl Iterator<Item = (Guid, *const c_void)> {
+        // Safety: The firmware guarantees `num_table_entries` entries in the configuration table
+        let tables = unsafe {
+            let raw = &*self.0;
+            slice::from_raw_parts(raw.config_table, raw.num_table_entries)
+        };
+
+        tables.iter().map(|t| (t.vendor_guid, t.vendor_table))
+    }
+
+    /// Returns the table installed with the given GUID in the configuration table, if any.
+    pub fn config_table(&self, guid: &Guid) -> Option<*const c_void> {
+        self.config_tables()
+            .find(|(g, _)| g == guid)
+            .map(|(_, table)| table)
+    }
+
+    /// Returns the ACPI RSDP, preferring the ACPI 2.0+ one.
+    pub fn acpi_rsdp(&self) -> Option<*const c_void> {
+        self.config_table(&config_table::ACPI_20_TABLE)
+            .or_else(|| self.config_table(&config_table::ACPI_TABLE))
+    }
+
+    pub fn smbios_entry_point(&self) -> Option<*const c_void> {
+        self.config_table(&config_table::SMBIOS_TABLE)
+    }
+
+    pub fn smbios3_entry_point(&self) -> Option<*const c_void> {
+        self.config_table(&config_table::SMBIOS3_TABLE)
+    }
+
+    pub fn device_tree(&self) -> Option<*const c_void> {
+        self.config_table(&config_table::DEVICE_TREE_TABLE)
+    }
+
+    pub fn as_ptr(&self) -> *const c_void {
+        self.0 as *const c_void
+    }
+
+    pub fn header(&self) -> &TableHeader {
+        // Safety: The system table stays valid for as long as PUB runs
+        unsafe { &(*self.0).hdr }
+    }
+
+    /// Revision of the UEFI specification the firmware conforms to
+    pub fn uefi_revision(&self) -> Revision {
+        self.header().revision()
+    }
+
+    /// Name of the firmware vendor, e.g. `EDK II`
+    pub fn firmware_vendor(&self) -> &CStr16 {
+        // Safety: The firmware sets a null-terminated string, which lives as long as the table
+        unsafe { CStr16::from_ptr((*self.0).firmware_vendor) }
+    }
+
+    /// Vendor-specific revision of the firmware
+    pub fn firmware_revision(&self) -> u32 {
+        unsafe { (*self.0).firmware_revision }
+    }
 }
 
 #[repr(C)]
@@ -79,19 +172,130 @@ pub struct TableHeader {
     _reserved: u32,
 }
 
+/// Offset of the `crc32` field, which is zeroed while computing the CRC
+const TABLE_CRC32_OFFSET: usize = 16;
+/// The largest table (boot services) is well below that, anything bigger is garbage
+const MAX_TABLE_SIZE: u32 = 0x1000;
+
+impl TableHeader {
+    /// Checks the signature, size and CRC32 of the table starting with this header.
+    pub fn validate(
+        &self,
+        signature: u64,
+        boot_services: &BootServices,
+    ) -> Result<(), TableHeaderError> {
+        if self.signature != signature {
+            return Err(TableHeaderError::Signature(self.signature));
+        }
+        let size = self.header_size;
+        if size < size_of::<Self>() as u32 || size > MAX_TABLE_SIZE {
+            return Err(TableHeaderError::Size(size));
+        }
+
+        // The table can't be modified in place, so the CRC is computed on a copy
+        let mut copy = AllocatedPool::<[u8]>::try_new(*boot_services, size as usize)?;
+        // Safety: The table is `header_size` bytes long, header included
+        let table =
+            unsafe { slice::from_raw_parts(self as *const Self as *const u8, size as usize) };
+        copy.as_mut().copy_from_slice(table);
+        copy.as_mut()[TABLE_CRC32_OFFSET..TABLE_CRC32_OFFSET + 4].fill(0);
+
+        let computed = boot_services.calculate_crc32(copy.as_ref())?;
+        if computed != self.crc32 {
+            return Err(TableHeaderError::Crc32 {
+                expected: self.crc32,
+                computed,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn revision(&self) -> Revision {
+        Revision(self.revision)
+    }
+}
+
+#[derive(Debug)]
+pub enum TableHeaderError {
+    EfiError(StatusError),
+    /// Signature doesn't match the table type
+    Signature(u64),
+    /// Header size is smaller than a header, or unreasonably large
+    Size(u32),
+    Crc32 {
+        expected: u32,
+        computed: u32,
+    },
+}
+
+impl Display for TableHeaderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TableHeaderError::EfiError(e) => write!(f, "error computing the CRC32: {}", e),
+            TableHeaderError::Signature(s) => write!(f, "invalid signature {:#018x}", s),
+            TableHeaderError::Size(s) => write!(f, "invalid table size {:#x}", s),
+            TableHeaderError::Crc32 { expected, computed } => write!(
+                f,
+                "CRC32 mismatch (header says {:#010x}, computed {:#010x})",
+                expected, computed
+            ),
+        }
+    }
+}
+
+impl From<StatusError> for TableHeaderError {
+    fn from(value: StatusError) -> Self {
+        Self::EfiError(value)
+    }
+}
+
+/// UEFI specification revision, as found in table headers. The minor revision holds two digits,
+/// e.g. 2.31 is 2.3.1 and 2.70 is 2.7.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Revision(u32);
+
+impl Revision {
+    /// First revision with `OpenProtocol`, `LocateProtocol` and the other EFI 1.1 boot services
+    /// (`ConnectController`, `CalculateCrc32`...)
+    pub const EFI_1_10: Self = Self::new(1, 10);
+
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self((major as u32) << 16 | minor as u32)
+    }
+
+    pub fn major(&self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+
+    pub fn minor(&self) -> u16 {
+        self.0 as u16
+    }
+}
+
+impl Display for Revision {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}", self.major(), self.minor() / 10)?;
+        match self.minor() % 10 {
+            0 => Ok(()),
+            patch => write!(f, ".{}", patch),
+        }
+    }
+}
+
 #[repr(C)]
 pub struct RawSystemTable {
     hdr: TableHeader,
     firmware_vendor: *const u16,
     firmware_revision: u32,
     console_in_handle: Handle,
-    con_in: *const c_void, // TODO
+    con_in: *mut Input,
     console_out_handle: Handle,
     con_out: *mut Output,
     std_err_handle: Handle,
-    std_err: *const c_void,
-    runtime_services: *const c_void, // TODO
+    std_err: *mut Output,
+    runtime_services: *mut RawRuntimeServices,
     boot_services: *mut RawBootServices,
     num_table_entries: usize,
-    config_table: *const c_void, // TODO
+    config_table: *const ConfigurationTable,
 }
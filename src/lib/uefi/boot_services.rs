@@ -1,37 +1,506 @@
-use core::{ffi::c_void, ptr};
+use core::{
+    ffi::c_void,
+    marker::PhantomData,
+    ptr::{self, NonNull},
+};
 
 use crate::uefi::status::StatusError;
 
 use super::{
+    event::{closure_trampoline, Event, EventNotifyFn, EventType, RawEvent, TimerDelay, Tpl},
+    helper::{self, AllocatedPool},
+    memory_map::{MemoryDescriptor, MemoryMap},
+    protocols::{
+        DevicePathProtocol, OpenProtocolAttributes, Protocol, ProtocolLocateError, ScopedProtocol,
+    },
     status::{EfiResult, Status},
-    AllocateType, Guid, Handle, MemoryType, PhysicalAddress, TableHeader,
+    string::{CStr16, PoolCStr16},
+    AllocateType, Guid, Handle, MemoryType, PhysicalAddress, Revision, TableHeader,
 };
 
+/// Extra descriptors allocated on top of the reported memory map size, since allocating the
+/// buffer itself may split a memory region.
+const EXTRA_MEMORY_DESCRIPTORS: usize = 8;
+
+/// Handles returned by the firmware, freed once dropped
+pub type HandleBuffer = AllocatedPool<[Handle]>;
+
+/// GUIDs of the protocols installed on a handle, freed once dropped
+pub struct ProtocolGuidBuffer(AllocatedPool<[*const Guid]>);
+
+impl ProtocolGuidBuffer {
+    pub fn len(&self) -> usize {
+        self.0.as_ref().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Guid> {
+        // Safety: The firmware returns pointers to the GUIDs of installed protocols
+        self.0.as_ref().iter().map(|g| unsafe { &**g })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum SearchType<'a> {
+    AllHandles,
+    /// Handles on which a protocol was installed since the last search, one at a time (see
+    /// `BootServices::register_protocol_notify`)
+    ByRegisterNotify(ProtocolRegistration<'a>),
+    ByProtocol(&'a Guid),
+}
+
+impl SearchType<'_> {
+    /// Search type, protocol and search key
+    fn to_raw(self) -> (usize, *const Guid, *const c_void) {
+        match self {
+            SearchType::AllHandles => (0, ptr::null(), ptr::null()),
+            SearchType::ByRegisterNotify(registration) => {
+                (1, ptr::null(), registration.key.as_ptr())
+            }
+            SearchType::ByProtocol(guid) => (2, guid, ptr::null()),
+        }
+    }
+}
+
+/// Key returned by `register_protocol_notify`, valid until its event is closed
+#[derive(Clone, Copy)]
+pub struct ProtocolRegistration<'a> {
+    key: NonNull<c_void>,
+    _event: PhantomData<&'a Event>,
+}
+
+/// What a started image returned, or passed to `Exit`
+pub struct ImageExit {
+    pub status: Status,
+    /// Null-terminated string describing the exit, possibly followed by binary data
+    pub data: Option<PoolCStr16>,
+}
+
+/// Maximum number of events that can be waited on at once
+pub const MAX_WAIT_EVENTS: usize = 16;
+
 #[repr(transparent)]
 #[derive(Clone, Copy)]
 pub struct BootServices(*mut RawBootServices);
 
 impl BootServices {
+    /// `BOOTSERV`
+    pub const SIGNATURE: u64 = 0x5652_4553_544F_4F42;
+
     pub(crate) fn from_ptr(ptr: *mut RawBootServices) -> Self {
         Self(ptr)
     }
 
-    pub(crate) fn generic_handle_protocol(
+    pub fn header(&self) -> &TableHeader {
+        // Safety: The boot services table stays valid until boot services are exited
+        unsafe { &(*self.0).hdr }
+    }
+
+    /// Fails with `Unsupported` if the firmware predates `revision`: its table ends before the
+    /// services that revision added.
+    fn require(&self, revision: Revision) -> EfiResult<()> {
+        if self.header().revision() < revision {
+            return Err(StatusError::Unsupported);
+        }
+        Ok(())
+    }
+
+    /// Raises the task priority level to `new`, returns the previous one. Notifications at or below
+    /// `new` are held until the TPL is restored. See `TplGuard` for a safe wrapper.
+    ///
+    /// # Safety
+    /// `new` must not be lower than the current TPL, and the previous TPL must be restored with
+    /// `restore_tpl` once done.
+    pub unsafe fn raise_tpl(&self, new: Tpl) -> Tpl {
+        unsafe { ((*self.0).raise_tpl)(new) }
+    }
+
+    /// # Safety
+    /// `old` must have been returned by `raise_tpl`, and be lower than or equal to the current TPL.
+    pub unsafe fn restore_tpl(&self, old: Tpl) {
+        unsafe { ((*self.0).restore_tpl)(old) }
+    }
+
+    pub fn calculate_crc32(&self, data: &[u8]) -> EfiResult<u32> {
+        self.require(Revision::EFI_1_10)?;
+        let mut crc32 = 0;
+        // Safety: The firmware only reads `data.len()` bytes
+        unsafe {
+            ((*self.0).calculate_crc32)(data.as_ptr() as *const c_void, data.len(), &mut crc32)
+        }
+        .to_result()?;
+        Ok(crc32)
+    }
+
+    /// Opens the protocol `P` on `handle`, on behalf of our image (see
+    /// `helper::register_image_handle`).
+    pub fn open_protocol<P: Protocol>(
         &self,
         handle: Handle,
+        attributes: OpenProtocolAttributes,
+    ) -> Result<ScopedProtocol<'_, P>, ProtocolLocateError> {
+        self.require(Revision::EFI_1_10)
+            .map_err(|_| ProtocolLocateError::Unsupported)?;
+        let agent = helper::image_handle()
+            .ok_or(ProtocolLocateError::Error(StatusError::InvalidParameter))?;
+
+        let mut interface: *mut c_void = ptr::null_mut();
+        // Safety: Handled on the EFI side, our data structures aren't null
+        let result = unsafe {
+            ((*self.0).open_protocol)(
+                handle,
+                &P::GUID,
+                &mut interface,
+                agent,
+                None,
+                attributes.bits(),
+            )
+        }
+        .to_result();
+
+        match result {
+            Ok(()) => match NonNull::new(interface as *mut P) {
+                Some(interface) => Ok(ScopedProtocol::new(interface, handle, agent, self)),
+                None => Err(ProtocolLocateError::Unsupported),
+            },
+            Err(StatusError::Unsupported) => Err(ProtocolLocateError::Unsupported),
+            Err(e) => Err(ProtocolLocateError::Error(e)),
+        }
+    }
+
+    /// Returns every handle matching `search_type`. Returns `Err(NotFound)` if no handle matches.
+    pub fn locate_handle_buffer(&self, search_type: SearchType) -> EfiResult<HandleBuffer> {
+        self.require(Revision::EFI_1_10)?;
+        let (search_type, protocol, search_key) = search_type.to_raw();
+        let mut count = 0;
+        let mut buffer: *mut Handle = ptr::null_mut();
+
+        unsafe {
+            ((*self.0).locate_handle_buffer)(
+                search_type,
+                protocol,
+                search_key,
+                &mut count,
+                &mut buffer,
+            )
+        }
+        .to_result()?;
+
+        if buffer.is_null() {
+            return Err(StatusError::NotFound);
+        }
+        // Safety: The firmware allocated a pool containing `count` handles
+        Ok(unsafe { AllocatedPool::from_raw_parts(*self, buffer, count) })
+    }
+
+    /// Fills `buffer` with the handles matching `search_type`, returns how many were found. Fails
+    /// with `BufferTooSmall` if they don't fit, and `NotFound` if there is none.
+    pub fn locate_handle(
+        &self,
+        search_type: SearchType,
+        buffer: &mut [Option<Handle>],
+    ) -> EfiResult<usize> {
+        let (search_type, protocol, search_key) = search_type.to_raw();
+        let mut buffer_size = size_of_val(buffer);
+        unsafe {
+            ((*self.0).locate_handle)(
+                search_type,
+                protocol,
+                search_key,
+                &mut buffer_size,
+                buffer.as_mut_ptr(),
+            )
+        }
+        .to_result()?;
+
+        Ok(buffer_size / size_of::<Handle>())
+    }
+
+    /// Returns the next handle the protocol of `registration` was installed on, if any.
+    pub fn next_registered_handle(&self, registration: ProtocolRegistration) -> Option<Handle> {
+        let mut buffer = [None];
+        self.locate_handle(SearchType::ByRegisterNotify(registration), &mut buffer)
+            .ok()?;
+        buffer[0]
+    }
+
+    /// Signals `event` whenever `protocol` gets installed on a handle. The new handles are found
+    /// with `SearchType::ByRegisterNotify`.
+    pub fn register_protocol_notify<'e>(
+        &self,
         protocol: &Guid,
-    ) -> EfiResult<Option<*const c_void>> {
-        let mut interface: *const c_void = ptr::null();
-        let interface_ptr: *mut *const c_void = &mut interface;
-        // // Safety: Handled on the EFI side, our data structures aren't null
-        let result =
-            unsafe { ((*self.0).handle_protocol)(handle, protocol, interface_ptr) }.to_result();
+        event: &'e Event,
+    ) -> EfiResult<ProtocolRegistration<'e>> {
+        let mut key: *mut c_void = ptr::null_mut();
+        unsafe { ((*self.0).register_protocol_notify)(protocol, event.raw(), &mut key) }
+            .to_result()?;
+
+        let key = NonNull::new(key).ok_or(StatusError::InvalidParameter)?;
+        Ok(ProtocolRegistration {
+            key,
+            _event: PhantomData,
+        })
+    }
+
+    /// Returns the first interface of the protocol `P` found on any handle. The interface isn't
+    /// opened, so it should only be used for protocols that never get uninstalled.
+    pub fn locate_protocol<P: Protocol>(&self) -> Result<&P, ProtocolLocateError> {
+        self.require(Revision::EFI_1_10)
+            .map_err(|_| ProtocolLocateError::Unsupported)?;
+        let mut interface: *mut c_void = ptr::null_mut();
+        let result = unsafe { ((*self.0).locate_protocol)(&P::GUID, ptr::null(), &mut interface) }
+            .to_result();
 
         match result {
-            Ok(()) => Ok(Some(interface)),
-            Err(StatusError::Unsupported) => Ok(None),
-            Err(e) => Err(e),
+            // Safety: The firmware returned a valid interface for the protocol
+            Ok(()) if !interface.is_null() => Ok(unsafe { &*(interface as *const P) }),
+            Ok(()) | Err(StatusError::NotFound) => Err(ProtocolLocateError::Unsupported),
+            Err(e) => Err(ProtocolLocateError::Error(e)),
+        }
+    }
+
+    /// Returns the GUIDs of every protocol installed on `handle`.
+    pub fn protocols_per_handle(&self, handle: Handle) -> EfiResult<ProtocolGuidBuffer> {
+        self.require(Revision::EFI_1_10)?;
+        let mut buffer: *mut *const Guid = ptr::null_mut();
+        let mut count = 0;
+        unsafe { ((*self.0).protocols_per_handle)(handle, &mut buffer, &mut count) }.to_result()?;
+
+        if buffer.is_null() {
+            return Err(StatusError::NotFound);
+        }
+        // Safety: The firmware allocated a pool containing `count` GUID pointers
+        let pool = unsafe { AllocatedPool::from_raw_parts(*self, buffer, count) };
+        Ok(ProtocolGuidBuffer(pool))
+    }
+
+    /// Installs the `(protocol, interface)` pairs on `handle`, or on a new handle if `None`, and
+    /// returns the handle. Nothing is installed if any of them fails, and installing a device path
+    /// which is already installed on another handle fails with `AlreadyStarted`.
+    ///
+    /// # Safety
+    /// Every interface must be valid for its protocol, until it gets uninstalled.
+    pub unsafe fn install_multiple_protocol_interfaces(
+        &self,
+        handle: Option<Handle>,
+        interfaces: &[(&Guid, *const c_void)],
+    ) -> EfiResult<Handle> {
+        self.require(Revision::EFI_1_10)?;
+        let mut handle = handle;
+        let install = unsafe { (*self.0).install_multiple_protocol_interfaces };
+        let end = ptr::null::<Guid>();
+        let status = match *interfaces {
+            [(g1, i1)] => unsafe { install(&mut handle, g1 as *const Guid, i1, end) },
+            [(g1, i1), (g2, i2)] => unsafe {
+                install(
+                    &mut handle,
+                    g1 as *const Guid,
+                    i1,
+                    g2 as *const Guid,
+                    i2,
+                    end,
+                )
+            },
+            [(g1, i1), (g2, i2), (g3, i3)] => unsafe {
+                install(
+                    &mut handle,
+                    g1 as *const Guid,
+                    i1,
+                    g2 as *const Guid,
+                    i2,
+                    g3 as *const Guid,
+                    i3,
+                    end,
+                )
+            },
+            // Nobody needs more yet
+            _ => return Err(StatusError::InvalidParameter),
+        };
+        status.to_result()?;
+
+        handle.ok_or(StatusError::InvalidParameter)
+    }
+
+    /// Uninstalls the `(protocol, interface)` pairs from `handle`. Nothing is uninstalled if any of
+    /// them fails, e.g. because a driver has the protocol open.
+    ///
+    /// # Safety
+    /// The interfaces must not be in use anymore.
+    pub unsafe fn uninstall_multiple_protocol_interfaces(
+        &self,
+        handle: Handle,
+        interfaces: &[(&Guid, *const c_void)],
+    ) -> EfiResult<()> {
+        self.require(Revision::EFI_1_10)?;
+        let uninstall = unsafe { (*self.0).uninstall_multiple_protocol_interfaces };
+        let end = ptr::null::<Guid>();
+        let status = match *interfaces {
+            [(g1, i1)] => unsafe { uninstall(handle, g1 as *const Guid, i1, end) },
+            [(g1, i1), (g2, i2)] => unsafe {
+                uninstall(handle, g1 as *const Guid, i1, g2 as *const Guid, i2, end)
+            },
+            [(g1, i1), (g2, i2), (g3, i3)] => unsafe {
+                uninstall(
+                    handle,
+                    g1 as *const Guid,
+                    i1,
+                    g2 as *const Guid,
+                    i2,
+                    g3 as *const Guid,
+                    i3,
+                    end,
+                )
+            },
+            _ => return Err(StatusError::InvalidParameter),
+        };
+        status.to_result()
+    }
+
+    /// Adds, updates (or removes, if `table` is null) an entry of the configuration table.
+    ///
+    /// # Safety
+    /// `table` must stay valid for as long as it is installed, so it is usually allocated as
+    /// runtime or ACPI reclaim memory.
+    pub unsafe fn install_configuration_table(
+        &self,
+        guid: &Guid,
+        table: *const c_void,
+    ) -> EfiResult<()> {
+        unsafe { ((*self.0).install_configuration_table)(guid, table) }.to_result()
+    }
+
+    /// Loads an EFI image from `source`, or from the file at `device_path` if there is no source.
+    /// `boot_policy` is only relevant when loading from a file, it tells the firmware the request
+    /// comes from the boot manager.
+    pub fn load_image(
+        &self,
+        boot_policy: bool,
+        parent_image: Handle,
+        device_path: Option<&DevicePathProtocol>,
+        source: Option<&[u8]>,
+    ) -> EfiResult<Handle> {
+        let (source_buffer, source_size) =
+            source.map_or((ptr::null(), 0), |s| (s.as_ptr() as *const c_void, s.len()));
+        let mut image = None;
+        let result = unsafe {
+            ((*self.0).load_image)(
+                boot_policy.into(),
+                parent_image,
+                device_path.map_or(ptr::null(), |p| p),
+                source_buffer,
+                source_size,
+                &mut image,
+            )
+        }
+        .to_result();
+
+        match (result, image) {
+            (Ok(()), Some(image)) => Ok(image),
+            (Ok(()), None) => Err(StatusError::LoadError),
+            (Err(e), image) => {
+                // On a security violation, the image is loaded but can't be started
+                if let Some(image) = image.filter(|_| e == StatusError::SecurityViolation) {
+                    let _ = self.unload_image(image);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Runs a loaded image, until it returns or calls `Exit`. Applications are unloaded once they
+    /// exit.
+    pub fn start_image(&self, image: Handle) -> ImageExit {
+        let mut exit_data_size = 0;
+        let mut exit_data: *mut u16 = ptr::null_mut();
+        let status = unsafe { ((*self.0).start_image)(image, &mut exit_data_size, &mut exit_data) };
+
+        let data = (!exit_data.is_null()).then(|| {
+            // Safety: The image allocated a pool with a null-terminated string (possibly followed
+            // by binary data), which the caller is responsible for
+            unsafe {
+                let pool = AllocatedPool::from_raw_parts(*self, exit_data, exit_data_size / 2);
+                PoolCStr16::new(pool)
+            }
+        });
+        ImageExit { status, data }
+    }
+
+    /// Unloads our image and gives control back to whoever started it (usually the boot manager,
+    /// which moves on to the next boot option), along with `status` and an optional description.
+    /// Everything the image allocated should be freed beforehand.
+    pub fn exit(&self, image: Handle, status: Status, data: Option<&CStr16>) -> ! {
+        // Exit data is freed by the caller of StartImage, so it must be a pool
+        let data = data.and_then(|data| {
+            let len = data.len() + 1;
+            let mut pool = AllocatedPool::<[u16]>::try_new(*self, len).ok()?;
+            let chars = data.chars().map(|c| c as u16).chain([0]);
+            for (dst, c) in pool.as_mut().iter_mut().zip(chars) {
+                *dst = c;
+            }
+            Some((len * 2, pool.leak() as *const u16))
+        });
+        let (data_size, data) = data.unwrap_or((0, ptr::null()));
+
+        // Safety: The exit data (if any) is a pool holding a null-terminated string
+        unsafe { ((*self.0).exit)(image, status, data_size, data) };
+        // Exit only returns if `image` isn't the running image, which leaves nowhere to go
+        loop {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Unloads an image which wasn't started, or a driver which supports being unloaded.
+    pub fn unload_image(&self, image: Handle) -> EfiResult<()> {
+        unsafe { ((*self.0).unload_image)(image) }.to_result()
+    }
+
+    /// Connects the drivers which support `controller` (only `driver` if set). `remaining_path`
+    /// restricts which children get created, and `recursive` connects every child as well. Fails
+    /// with `NotFound` if no driver could be connected.
+    pub fn connect_controller(
+        &self,
+        controller: Handle,
+        driver: Option<Handle>,
+        remaining_path: Option<&DevicePathProtocol>,
+        recursive: bool,
+    ) -> EfiResult<()> {
+        self.require(Revision::EFI_1_10)?;
+        // The driver list is null-terminated
+        let drivers = [driver, None];
+        let drivers = match driver {
+            Some(_) => drivers.as_ptr(),
+            None => ptr::null(),
+        };
+        let remaining_path = remaining_path.map_or(ptr::null(), |p| p as *const _);
+        unsafe {
+            ((*self.0).connect_controller)(controller, drivers, remaining_path, recursive as u8)
         }
+        .to_result()
+    }
+
+    /// Disconnects `driver` (every driver if unset) from `controller`, destroying `child` (every
+    /// child if unset).
+    pub fn disconnect_controller(
+        &self,
+        controller: Handle,
+        driver: Option<Handle>,
+        child: Option<Handle>,
+    ) -> EfiResult<()> {
+        self.require(Revision::EFI_1_10)?;
+        unsafe { ((*self.0).disconnect_controller)(controller, driver, child) }.to_result()
+    }
+
+    pub(crate) fn close_protocol(
+        &self,
+        handle: Handle,
+        protocol: &Guid,
+        agent: Handle,
+        controller: Option<Handle>,
+    ) -> EfiResult<()> {
+        unsafe { ((*self.0).close_protocol)(handle, protocol, agent, controller) }.to_result()
     }
 
     /// Returns `Ok(ptr)` if the call succeeded, where `ptr` points to the start of the pool.
@@ -84,14 +553,220 @@ impl BootServices {
     pub fn free_pages(&self, memory: PhysicalAddress, pages: usize) -> EfiResult<()> {
         unsafe { ((*self.0).free_pages)(memory, pages) }.to_result()
     }
+
+    /// Creates an event. Notification functions are only called for `NotifyWait` and
+    /// `NotifySignal` events, and run at `notify_tpl`.
+    ///
+    /// # Safety
+    /// `notify` must be safe to call with `context` at `notify_tpl`, for as long as the event
+    /// exists.
+    pub unsafe fn create_event(
+        &self,
+        event_type: EventType,
+        notify_tpl: Tpl,
+        notify: Option<EventNotifyFn>,
+        context: *mut c_void,
+    ) -> EfiResult<Event> {
+        if notify.is_some() && !notify_tpl.is_valid_notify_tpl() {
+            return Err(StatusError::InvalidParameter);
+        }
+
+        let mut event: Option<RawEvent> = None;
+        unsafe {
+            ((*self.0).create_event)(event_type.bits(), notify_tpl, notify, context, &mut event)
+        }
+        .to_result()?;
+
+        // A successful call always returns an event
+        let event = event.ok_or(StatusError::InvalidParameter)?;
+        Ok(Event::new(event, *self))
+    }
+
+    /// Creates a timer event without any notification function, to be used with `wait_for_any`
+    /// or `Event::check`.
+    pub fn create_timer(&self) -> EfiResult<Event> {
+        // Safety: No notification function
+        unsafe { self.create_event(EventType::Timer, Tpl::APPLICATION, None, ptr::null_mut()) }
+    }
+
+    /// Creates an event without any notification function, signaled by someone else (e.g. through
+    /// `register_protocol_notify`). To be used with `wait_for_any` or `Event::check`.
+    pub fn create_plain_event(&self) -> EfiResult<Event> {
+        // Safety: No notification function
+        unsafe { self.create_event(EventType::empty(), Tpl::APPLICATION, None, ptr::null_mut()) }
+    }
+
+    /// Creates an event calling `callback` at `notify_tpl` when notified. `event_type` should
+    /// include `NotifySignal` or `NotifyWait`.
+    pub fn create_callback_event<F: Fn() + Sync>(
+        &self,
+        event_type: EventType,
+        notify_tpl: Tpl,
+        callback: &'static F,
+    ) -> EfiResult<Event> {
+        // Safety: The callback lives forever, and can be shared between TPLs since it's `Sync`
+        unsafe {
+            self.create_event(
+                event_type,
+                notify_tpl,
+                Some(closure_trampoline::<F>),
+                callback as *const F as *mut c_void,
+            )
+        }
+    }
+
+    pub fn set_timer(&self, event: &Event, delay: TimerDelay) -> EfiResult<()> {
+        let (delay_type, trigger_time) = delay.to_raw();
+        unsafe { ((*self.0).set_timer)(event.raw(), delay_type, trigger_time) }.to_result()
+    }
+
+    /// Blocks until one of the events is signaled, and returns its index. Can only be called at
+    /// `Tpl::APPLICATION`, and with at most `MAX_WAIT_EVENTS` events.
+    pub fn wait_for_any(&self, events: &[&Event]) -> EfiResult<usize> {
+        if events.is_empty() || events.len() > MAX_WAIT_EVENTS {
+            return Err(StatusError::InvalidParameter);
+        }
+
+        let mut raw_events: [Option<RawEvent>; MAX_WAIT_EVENTS] = [None; MAX_WAIT_EVENTS];
+        for (raw, event) in raw_events.iter_mut().zip(events) {
+            *raw = Some(event.raw());
+        }
+
+        let mut index = 0;
+        // Safety: The first `events.len()` entries are valid events
+        unsafe { ((*self.0).wait_for_event)(events.len(), raw_events.as_ptr(), &mut index) }
+            .to_result()?;
+
+        Ok(index)
+    }
+
+    pub fn check_event(&self, event: &Event) -> EfiResult<bool> {
+        match unsafe { ((*self.0).check_event)(event.raw()) }.to_result() {
+            Ok(()) => Ok(true),
+            Err(StatusError::NotReady) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn signal_event(&self, event: &Event) -> EfiResult<()> {
+        unsafe { ((*self.0).signal_event)(event.raw()) }.to_result()
+    }
+
+    pub(crate) fn close_event(&self, event: RawEvent) -> EfiResult<()> {
+        unsafe { ((*self.0).close_event)(event) }.to_result()
+    }
+
+    /// Arms the watchdog timer, which resets the platform after `timeout` seconds. Codes up to
+    /// 0xFFFF are reserved for the firmware. `data` is a description of the reason of the reset,
+    /// which may get logged by the firmware.
+    pub fn set_watchdog_timer(
+        &self,
+        timeout: usize,
+        code: u64,
+        data: Option<&CStr16>,
+    ) -> EfiResult<()> {
+        let (size, data) = match data {
+            Some(s) => (size_of_val(s), s.as_ptr()),
+            None => (0, ptr::null()),
+        };
+
+        unsafe { ((*self.0).set_watchdog_timer)(timeout, code, size, data) }.to_result()
+    }
+
+    pub fn disable_watchdog_timer(&self) -> EfiResult<()> {
+        self.set_watchdog_timer(0, 0, None)
+    }
+
+    /// Busy-waits for at least the given number of microseconds.
+    pub fn stall(&self, microseconds: usize) -> EfiResult<()> {
+        unsafe { ((*self.0).stall)(microseconds) }.to_result()
+    }
+
+    /// Returns the current size of the memory map and the size of a single descriptor, in bytes.
+    fn memory_map_size(&self) -> EfiResult<(usize, usize)> {
+        let mut map_size = 0;
+        let mut map_key = 0;
+        let mut descriptor_size = 0;
+        let mut descriptor_version = 0;
+
+        let res = unsafe {
+            ((*self.0).get_memory_map)(
+                &mut map_size,
+                ptr::null_mut(),
+                &mut map_key,
+                &mut descriptor_size,
+                &mut descriptor_version,
+            )
+        }
+        .to_result();
+
+        match res {
+            Err(StatusError::BufferTooSmall) => Ok((map_size, descriptor_size)),
+            Ok(()) => Ok((map_size, descriptor_size)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Allocates a buffer and fills it with the current memory map.
+    pub fn memory_map(&self) -> EfiResult<MemoryMap> {
+        let (size, descriptor_size) = self.memory_map_size()?;
+        let capacity = size + EXTRA_MEMORY_DESCRIPTORS * descriptor_size;
+        let buffer = AllocatedPool::<[u8]>::try_new(*self, capacity)?;
+
+        let mut map = MemoryMap::new(buffer, 0, 0, descriptor_size, 0);
+        self.refresh_memory_map(&mut map)?;
+        Ok(map)
+    }
+
+    /// Gets the current memory map again, reusing the buffer of `map`. Does not allocate memory.
+    pub fn refresh_memory_map(&self, map: &mut MemoryMap) -> EfiResult<()> {
+        let buffer = map.buffer_mut();
+        let mut map_size = buffer.len();
+        let mut map_key = 0;
+        let mut descriptor_size = 0;
+        let mut descriptor_version = 0;
+
+        // Safety: `map_size` is the size of the buffer in bytes
+        unsafe {
+            ((*self.0).get_memory_map)(
+                &mut map_size,
+                buffer.as_mut_ptr() as *mut MemoryDescriptor,
+                &mut map_key,
+                &mut descriptor_size,
+                &mut descriptor_version,
+            )
+        }
+        .to_result()?;
+
+        map.update(map_size, map_key, descriptor_size, descriptor_version);
+        Ok(())
+    }
+
+    /// Terminates boot services. If the memory map changed since `map` was obtained, it is
+    /// refreshed and another attempt is made.
+    ///
+    /// # Safety
+    /// On success, every `BootServices` copy and every object relying on them (pools, protocols,
+    /// console output) becomes invalid. Nothing allocated by boot services may be freed anymore.
+    pub unsafe fn exit_boot_services(&self, image: Handle, map: &mut MemoryMap) -> EfiResult<()> {
+        let res = unsafe { ((*self.0).exit_boot_services)(image, map.key()) }.to_result();
+        match res {
+            Err(StatusError::InvalidParameter) => {
+                // Map key is stale, only GetMemoryMap can be called until we succeed
+                self.refresh_memory_map(map)?;
+                unsafe { ((*self.0).exit_boot_services)(image, map.key()) }.to_result()
+            }
+            res => res,
+        }
+    }
 }
 
 #[repr(C)]
 pub(crate) struct RawBootServices {
     hdr: TableHeader,
     // Task Priority Services
-    raise_tpl: *const c_void,
-    restore_tpl: *const c_void,
+    raise_tpl: unsafe extern "efiapi" fn(new_tpl: Tpl) -> Tpl,
+    restore_tpl: unsafe extern "efiapi" fn(old_tpl: Tpl),
 
     // Memory Services
     allocate_pages: unsafe extern "efiapi" fn(
@@ -101,7 +776,13 @@ pub(crate) struct RawBootServices {
         address: *mut PhysicalAddress,
     ) -> Status,
     free_pages: unsafe extern "efiapi" fn(memory: PhysicalAddress, pages: usize) -> Status,
-    get_memory_map: *const c_void,
+    get_memory_map: unsafe extern "efiapi" fn(
+        memory_map_size: *mut usize,
+        memory_map: *mut MemoryDescriptor,
+        map_key: *mut usize,
+        descriptor_size: *mut usize,
+        descriptor_version: *mut u32,
+    ) -> Status,
     allocate_pool: unsafe extern "efiapi" fn(
         pool_type: MemoryType,
         size: usize,
@@ -110,58 +791,136 @@ pub(crate) struct RawBootServices {
     free_pool: unsafe extern "efiapi" fn(buffer: *mut c_void) -> Status,
 
     // Event & Timer Services
-    create_event: *const c_void,
-    set_timer: *const c_void,
-    wait_for_event: *const c_void,
-    signal_event: *const c_void,
-    close_event: *const c_void,
-    check_event: *const c_void,
+    create_event: unsafe extern "efiapi" fn(
+        event_type: u32,
+        notify_tpl: Tpl,
+        notify_function: Option<EventNotifyFn>,
+        notify_context: *mut c_void,
+        event: *mut Option<RawEvent>,
+    ) -> Status,
+    set_timer:
+        unsafe extern "efiapi" fn(event: RawEvent, delay_type: u32, trigger_time: u64) -> Status,
+    wait_for_event: unsafe extern "efiapi" fn(
+        number_of_events: usize,
+        event: *const Option<RawEvent>,
+        index: *mut usize,
+    ) -> Status,
+    signal_event: unsafe extern "efiapi" fn(event: RawEvent) -> Status,
+    close_event: unsafe extern "efiapi" fn(event: RawEvent) -> Status,
+    check_event: unsafe extern "efiapi" fn(event: RawEvent) -> Status,
 
     // Protocol Handler Services
     install_protocol_interface: *const c_void,
     reinstall_protocol_interface: *const c_void,
     uninstall_protocol_interface: *const c_void,
-    handle_protocol: unsafe extern "efiapi" fn(
-        handle: Handle,
+    handle_protocol: *const c_void,
+    _reserved: *const c_void,
+    register_protocol_notify: unsafe extern "efiapi" fn(
         protocol: *const Guid,
-        interface: *mut *const c_void,
+        event: RawEvent,
+        registration: *mut *mut c_void,
+    ) -> Status,
+    locate_handle: unsafe extern "efiapi" fn(
+        search_type: usize,
+        protocol: *const Guid,
+        search_key: *const c_void,
+        buffer_size: *mut usize,
+        buffer: *mut Option<Handle>,
     ) -> Status,
-    _reserved: *const c_void,
-    register_protocol_notify: *const c_void,
-    locate_handle: *const c_void,
     locate_device_path: *const c_void,
-    install_configuration_table: *const c_void,
+    install_configuration_table:
+        unsafe extern "efiapi" fn(guid: *const Guid, table: *const c_void) -> Status,
 
     // Image Services
-    load_image: *const c_void,
-    start_image: *const c_void,
-    exit: *const c_void,
-    unload_image: *const c_void,
-    exit_boot_services: *const c_void,
+    load_image: unsafe extern "efiapi" fn(
+        boot_policy: u8,
+        parent_image_handle: Handle,
+        device_path: *const DevicePathProtocol,
+        source_buffer: *const c_void,
+        source_size: usize,
+        image_handle: *mut Option<Handle>,
+    ) -> Status,
+    start_image: unsafe extern "efiapi" fn(
+        image_handle: Handle,
+        exit_data_size: *mut usize,
+        exit_data: *mut *mut u16,
+    ) -> Status,
+    exit: unsafe extern "efiapi" fn(
+        image_handle: Handle,
+        exit_status: Status,
+        exit_data_size: usize,
+        exit_data: *const u16,
+    ) -> Status,
+    unload_image: unsafe extern "efiapi" fn(image_handle: Handle) -> Status,
+    exit_boot_services: unsafe extern "efiapi" fn(image_handle: Handle, map_key: usize) -> Status,
 
     // Miscellaneous Services
     get_next_monotonic_count: *const c_void,
-    stall: *const c_void,
-    set_watchdog_timer: *const c_void,
+    stall: unsafe extern "efiapi" fn(microseconds: usize) -> Status,
+    set_watchdog_timer: unsafe extern "efiapi" fn(
+        timeout: usize,
+        watchdog_code: u64,
+        data_size: usize,
+        watchdog_data: *const u16,
+    ) -> Status,
 
     // DriverSupport Services
-    connect_controller: *const c_void,
-    disconnect_controller: *const c_void,
+    connect_controller: unsafe extern "efiapi" fn(
+        controller_handle: Handle,
+        driver_image_handle: *const Option<Handle>,
+        remaining_device_path: *const DevicePathProtocol,
+        recursive: u8,
+    ) -> Status,
+    disconnect_controller: unsafe extern "efiapi" fn(
+        controller_handle: Handle,
+        driver_image_handle: Option<Handle>,
+        child_handle: Option<Handle>,
+    ) -> Status,
 
     // Open and Close Protocol Services
-    open_protocol: *const c_void,
-    close_protocol: *const c_void,
+    open_protocol: unsafe extern "efiapi" fn(
+        handle: Handle,
+        protocol: *const Guid,
+        interface: *mut *mut c_void,
+        agent_handle: Handle,
+        controller_handle: Option<Handle>,
+        attributes: u32,
+    ) -> Status,
+    close_protocol: unsafe extern "efiapi" fn(
+        handle: Handle,
+        protocol: *const Guid,
+        agent_handle: Handle,
+        controller_handle: Option<Handle>,
+    ) -> Status,
     open_protocol_information: *const c_void,
 
     // Library Services
-    protocols_per_handle: *const c_void,
-    locate_handle_buffer: *const c_void,
-    locate_protocol: *const c_void,
-    install_multiple_protocol_interfaces: *const c_void,
-    uninstall_multiple_protocol_interfaces: *const c_void,
+    protocols_per_handle: unsafe extern "efiapi" fn(
+        handle: Handle,
+        protocol_buffer: *mut *mut *const Guid,
+        protocol_buffer_count: *mut usize,
+    ) -> Status,
+    locate_handle_buffer: unsafe extern "efiapi" fn(
+        search_type: usize,
+        protocol: *const Guid,
+        search_key: *const c_void,
+        no_handles: *mut usize,
+        buffer: *mut *mut Handle,
+    ) -> Status,
+    locate_protocol: unsafe extern "efiapi" fn(
+        protocol: *const Guid,
+        registration: *const c_void,
+        interface: *mut *mut c_void,
+    ) -> Status,
+    // Both take (GUID, interface) pairs, terminated by a null GUID
+    install_multiple_protocol_interfaces:
+        unsafe extern "efiapi" fn(handle: *mut Option<Handle>, ...) -> Status,
+    uninstall_multiple_protocol_interfaces:
+        unsafe extern "efiapi" fn(handle: Handle, ...) -> Status,
 
     // 32-bit CRC Services
-    calculate_crc32: *const c_void,
+    calculate_crc32:
+        unsafe extern "efiapi" fn(data: *const c_void, data_size: usize, crc32: *mut u32) -> Status,
 
     // Miscellaneous Services
     copy_mem: *const c_void,
@@ -0,0 +1,26 @@
+use core::ffi::c_void;
+
+use crate::guid;
+
+use super::Guid;
+
+/// ACPI 1.0 RSDP
+pub const ACPI_TABLE: Guid = guid!("EB9D2D30-2D88-11D3-9A16-0090273FC14D");
+/// ACPI 2.0+ RSDP
+pub const ACPI_20_TABLE: Guid = guid!("8868E871-E4F1-11D3-BC22-0080C73C8881");
+/// SMBIOS 2.x entry point
+pub const SMBIOS_TABLE: Guid = guid!("EB9D2D31-2D88-11D3-9A16-0090273FC14D");
+/// SMBIOS 3.x entry point
+pub const SMBIOS3_TABLE: Guid = guid!("F2FD1544-9794-4A2C-992E-E5BBCF20E394");
+/// Flattened device tree blob
+pub const DEVICE_TREE_TABLE: Guid = guid!("B1B621D5-F19C-41A5-830B-D9152C69AAE0");
+/// `EFI_MEMORY_ATTRIBUTES_TABLE`, describes the permissions of the runtime code and data regions
+pub const MEMORY_ATTRIBUTES_TABLE: Guid = guid!("DCFA911D-26EB-469F-A220-38B7DC461220");
+/// `EFI_DEBUG_IMAGE_INFO_TABLE_HEADER`, lists the loaded images for debuggers
+pub const DEBUG_IMAGE_INFO_TABLE: Guid = guid!("49152E77-1ADA-4764-B7A2-7AFEFED95E8B");
+
+#[repr(C)]
+pub struct ConfigurationTable {
+    pub vendor_guid: Guid,
+    pub vendor_table: *const c_void,
+}
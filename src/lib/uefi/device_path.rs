@@ -0,0 +1,462 @@
+use core::{fmt::Display, marker::PhantomData, slice};
+
+use super::{
+    protocols::DevicePathProtocol,
+    status::{EfiResult, StatusError},
+    string::CStr16,
+    Guid,
+};
+
+pub const HARDWARE_TYPE: u8 = 0x01;
+pub const ACPI_TYPE: u8 = 0x02;
+pub const MESSAGING_TYPE: u8 = 0x03;
+pub const MEDIA_TYPE: u8 = 0x04;
+pub const END_TYPE: u8 = 0x7F;
+
+/// Media sub-types
+pub const VENDOR_MEDIA_SUB_TYPE: u8 = 0x03;
+pub const FILE_PATH_SUB_TYPE: u8 = 0x04;
+
+pub const END_INSTANCE_SUB_TYPE: u8 = 0x01;
+pub const END_ENTIRE_SUB_TYPE: u8 = 0xFF;
+
+/// Every node starts with its type, sub-type and length (header included)
+pub(crate) const HEADER_LEN: usize = 4;
+
+/// PCI root bridges, `PNP0A03` and `PNP0A08` (PCI Express)
+const PCI_ROOT_HIDS: [u32; 2] = [eisa_id(*b"PNP", 0x0A03), eisa_id(*b"PNP", 0x0A08)];
+
+/// Compresses a PNP id the way ACPI device path nodes store it
+const fn eisa_id(vendor: [u8; 3], product: u16) -> u32 {
+    let vendor = ((vendor[0] - b'@') as u32) << 10
+        | ((vendor[1] - b'@') as u32) << 5
+        | (vendor[2] - b'@') as u32;
+    (product as u32) << 16 | vendor
+}
+
+/// A single node of a device path
+#[derive(Clone, Copy)]
+pub struct DevicePathNode<'a> {
+    pub node_type: u8,
+    pub sub_type: u8,
+    /// Data following the node header
+    pub data: &'a [u8],
+}
+
+impl<'a> DevicePathNode<'a> {
+    /// Separates the instances of a multi-instance path
+    pub fn is_end_instance(&self) -> bool {
+        self.node_type == END_TYPE && self.sub_type == END_INSTANCE_SUB_TYPE
+    }
+
+    /// Decodes the node. Nodes PUB doesn't know about, or which are too short for their kind, are
+    /// `Unknown`.
+    pub fn kind(&self) -> NodeKind<'a> {
+        self.decode().unwrap_or(NodeKind::Unknown)
+    }
+
+    fn decode(&self) -> Option<NodeKind<'a>> {
+        let d = self.data;
+        let kind = match (self.node_type, self.sub_type) {
+            (HARDWARE_TYPE, 0x01) => NodeKind::Pci {
+                function: *d.first()?,
+                device: *d.get(1)?,
+            },
+            (HARDWARE_TYPE, 0x04) => NodeKind::VendorHardware {
+                vendor: guid_at(d, 0)?,
+                data: &d[16..],
+            },
+            (HARDWARE_TYPE, 0x05) => NodeKind::Controller(u32_at(d, 0)?),
+            (ACPI_TYPE, 0x01) => NodeKind::Acpi {
+                hid: u32_at(d, 0)?,
+                uid: u32_at(d, 4)?,
+            },
+            (MESSAGING_TYPE, 0x02) => NodeKind::Scsi {
+                target: u16_at(d, 0)?,
+                lun: u16_at(d, 2)?,
+            },
+            (MESSAGING_TYPE, 0x05) => NodeKind::Usb {
+                parent_port: *d.first()?,
+                interface: *d.get(1)?,
+            },
+            (MESSAGING_TYPE, 0x0A) => NodeKind::VendorMessaging {
+                vendor: guid_at(d, 0)?,
+                data: &d[16..],
+            },
+            (MESSAGING_TYPE, 0x12) => NodeKind::Sata {
+                hba_port: u16_at(d, 0)?,
+                port_multiplier_port: u16_at(d, 2)?,
+                lun: u16_at(d, 4)?,
+            },
+            (MESSAGING_TYPE, 0x17) => NodeKind::Nvme {
+                namespace_id: u32_at(d, 0)?,
+                eui64: d.get(4..12)?.try_into().ok()?,
+            },
+            (MEDIA_TYPE, 0x01) => NodeKind::HardDrive(HardDrive {
+                partition_number: u32_at(d, 0)?,
+                start: u64_at(d, 4)?,
+                size: u64_at(d, 12)?,
+                signature: match (*d.get(36)?, *d.get(37)?) {
+                    (0x01, 0x01) => PartitionSignature::Mbr(u32_at(d, 20)?),
+                    (0x02, 0x02) => PartitionSignature::Gpt(guid_at(d, 20)?),
+                    _ => PartitionSignature::None,
+                },
+            }),
+            (MEDIA_TYPE, 0x02) => NodeKind::CdRom {
+                boot_entry: u32_at(d, 0)?,
+                start: u64_at(d, 4)?,
+                size: u64_at(d, 12)?,
+            },
+            (MEDIA_TYPE, VENDOR_MEDIA_SUB_TYPE) => NodeKind::VendorMedia {
+                vendor: guid_at(d, 0)?,
+                data: &d[16..],
+            },
+            (MEDIA_TYPE, FILE_PATH_SUB_TYPE) => NodeKind::FilePath(FilePath(d)),
+            (END_TYPE, END_INSTANCE_SUB_TYPE) => NodeKind::EndInstance,
+            _ => NodeKind::Unknown,
+        };
+        Some(kind)
+    }
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn guid_at(data: &[u8], offset: usize) -> Option<Guid> {
+    Some(Guid::from_bytes(
+        data.get(offset..offset + 16)?.try_into().ok()?,
+    ))
+}
+
+/// Decoded device path node
+#[derive(Clone, Copy)]
+pub enum NodeKind<'a> {
+    // Hardware
+    Pci {
+        function: u8,
+        device: u8,
+    },
+    VendorHardware {
+        vendor: Guid,
+        data: &'a [u8],
+    },
+    Controller(u32),
+    // ACPI
+    Acpi {
+        /// Compressed EISA id, e.g. `PNP0A03`
+        hid: u32,
+        uid: u32,
+    },
+    // Messaging
+    Scsi {
+        target: u16,
+        lun: u16,
+    },
+    Usb {
+        parent_port: u8,
+        interface: u8,
+    },
+    VendorMessaging {
+        vendor: Guid,
+        data: &'a [u8],
+    },
+    Sata {
+        hba_port: u16,
+        /// `0xFFFF` if the device is directly connected to the HBA
+        port_multiplier_port: u16,
+        lun: u16,
+    },
+    Nvme {
+        namespace_id: u32,
+        eui64: [u8; 8],
+    },
+    // Media
+    HardDrive(HardDrive),
+    CdRom {
+        boot_entry: u32,
+        start: u64,
+        size: u64,
+    },
+    VendorMedia {
+        vendor: Guid,
+        data: &'a [u8],
+    },
+    FilePath(FilePath<'a>),
+    EndInstance,
+    Unknown,
+}
+
+/// A partition, in sectors of the disk
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct HardDrive {
+    /// Starts at 1, 0 means the whole disk
+    pub partition_number: u32,
+    pub start: u64,
+    pub size: u64,
+    pub signature: PartitionSignature,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PartitionSignature {
+    None,
+    /// Disk signature of an MBR disk
+    Mbr(u32),
+    /// Unique GUID of a GPT partition
+    Gpt(Guid),
+}
+
+/// Null-terminated UCS-2 path of a File Path node. The data isn't guaranteed to be aligned, so it
+/// can't be borrowed as a `CStr16`.
+#[derive(Clone, Copy)]
+pub struct FilePath<'a>(&'a [u8]);
+
+impl FilePath<'_> {
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.0
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|c| *c != 0)
+            .map(|c| char::from_u32(c as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+}
+
+impl Display for FilePath<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.chars().try_for_each(|c| write!(f, "{}", c))
+    }
+}
+
+impl Display for DevicePathNode<'_> {
+    /// Uses the text form of the spec, falling back to the generic `Path(type,subtype,data)` for
+    /// unknown nodes.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.kind() {
+            NodeKind::Pci { function, device } => write!(f, "Pci({:#x},{:#x})", device, function),
+            NodeKind::VendorHardware { vendor, data } => write_vendor(f, "VenHw", vendor, data),
+            NodeKind::Controller(n) => write!(f, "Ctrl({:#x})", n),
+            NodeKind::Acpi { hid, uid } if PCI_ROOT_HIDS.contains(&hid) => {
+                write!(f, "PciRoot({:#x})", uid)
+            }
+            NodeKind::Acpi { hid, uid } => {
+                write!(f, "Acpi(")?;
+                write_eisa_id(f, hid)?;
+                write!(f, ",{:#x})", uid)
+            }
+            NodeKind::Scsi { target, lun } => write!(f, "Scsi({:#x},{:#x})", target, lun),
+            NodeKind::Usb {
+                parent_port,
+                interface,
+            } => write!(f, "USB({:#x},{:#x})", parent_port, interface),
+            NodeKind::VendorMessaging { vendor, data } => write_vendor(f, "VenMsg", vendor, data),
+            NodeKind::Sata {
+                hba_port,
+                port_multiplier_port,
+                lun,
+            } => write!(
+                f,
+                "Sata({:#x},{:#x},{:#x})",
+                hba_port, port_multiplier_port, lun
+            ),
+            NodeKind::Nvme {
+                namespace_id,
+                eui64,
+            } => {
+                write!(f, "NVMe({:#x},", namespace_id)?;
+                for (i, b) in eui64.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "-" };
+                    write!(f, "{}{:02X}", separator, b)?;
+                }
+                write!(f, ")")
+            }
+            NodeKind::HardDrive(hd) => {
+                write!(f, "HD({},", hd.partition_number)?;
+                match hd.signature {
+                    PartitionSignature::None => write!(f, "0,0")?,
+                    PartitionSignature::Mbr(sig) => write!(f, "MBR,{:#010x}", sig)?,
+                    PartitionSignature::Gpt(guid) => write!(f, "GPT,{}", guid)?,
+                }
+                write!(f, ",{:#x},{:#x})", hd.start, hd.size)
+            }
+            NodeKind::CdRom {
+                boot_entry,
+                start,
+                size,
+            } => write!(f, "CDROM({:#x},{:#x},{:#x})", boot_entry, start, size),
+            NodeKind::VendorMedia { vendor, data } => write_vendor(f, "VenMedia", vendor, data),
+            NodeKind::FilePath(path) => write!(f, "{}", path),
+            NodeKind::EndInstance => write!(f, ","),
+            NodeKind::Unknown => {
+                write!(f, "Path({},{}", self.node_type, self.sub_type)?;
+                if !self.data.is_empty() {
+                    write!(f, ",")?;
+                    write_hex(f, self.data)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn write_vendor(
+    f: &mut core::fmt::Formatter<'_>,
+    name: &str,
+    vendor: Guid,
+    data: &[u8],
+) -> core::fmt::Result {
+    write!(f, "{}({}", name, vendor)?;
+    if !data.is_empty() {
+        write!(f, ",")?;
+        write_hex(f, data)?;
+    }
+    write!(f, ")")
+}
+
+fn write_hex(f: &mut core::fmt::Formatter<'_>, data: &[u8]) -> core::fmt::Result {
+    data.iter().try_for_each(|b| write!(f, "{:02X}", b))
+}
+
+/// Decompresses an EISA id, e.g. `PNP0A03`
+fn write_eisa_id(f: &mut core::fmt::Formatter<'_>, id: u32) -> core::fmt::Result {
+    for shift in [10, 5, 0] {
+        let c = (b'@' + ((id >> shift) & 0x1F) as u8) as char;
+        write!(f, "{}", c)?;
+    }
+    write!(f, "{:04X}", id >> 16)
+}
+
+pub struct DevicePathNodes<'a> {
+    ptr: *const u8,
+    _marker: PhantomData<&'a DevicePathProtocol>,
+}
+
+impl<'a> DevicePathNodes<'a> {
+    /// # Safety
+    /// `path` must point to a device path ending with an End Entire node, valid for `'a`.
+    pub(crate) unsafe fn new(path: *const u8) -> Self {
+        Self {
+            ptr: path,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Iterator for DevicePathNodes<'a> {
+    type Item = DevicePathNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Safety: Every node starts with a header, and the path ends with an End Entire node
+        // (after which we stop reading)
+        let header = unsafe { slice::from_raw_parts(self.ptr, HEADER_LEN) };
+        let (node_type, sub_type) = (header[0], header[1]);
+        let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+        if (node_type == END_TYPE && sub_type == END_ENTIRE_SUB_TYPE) || len < HEADER_LEN {
+            // A malformed length would make us loop forever (or read garbage), stop there
+            return None;
+        }
+
+        // Safety: The node is `len` bytes long, header included
+        let data = unsafe { slice::from_raw_parts(self.ptr.add(HEADER_LEN), len - HEADER_LEN) };
+        let node = DevicePathNode {
+            node_type,
+            sub_type,
+            data,
+        };
+        // Safety: The next node follows this one
+        self.ptr = unsafe { self.ptr.add(len) };
+        Some(node)
+    }
+}
+
+/// Builds a device path in a caller-provided buffer. Every push fails with `BufferTooSmall` if the
+/// node doesn't fit, keeping room for the End node.
+pub struct DevicePathBuilder<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> DevicePathBuilder<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Size of a File Path node holding `path`
+    pub fn file_path_node_len(path: &CStr16) -> usize {
+        HEADER_LEN + (path.len() + 1) * 2
+    }
+
+    /// Appends a node with the given type and data
+    pub fn push(&mut self, node_type: u8, sub_type: u8, data: &[u8]) -> EfiResult<&mut Self> {
+        let node = self.reserve(node_type, sub_type, data.len())?;
+        node.copy_from_slice(data);
+        Ok(self)
+    }
+
+    /// Appends every node of `path`, e.g. the path of a device before a file path
+    pub fn push_path(&mut self, path: &DevicePathProtocol) -> EfiResult<&mut Self> {
+        for node in path.nodes() {
+            self.push(node.node_type, node.sub_type, node.data)?;
+        }
+        Ok(self)
+    }
+
+    pub fn push_file_path(&mut self, path: &CStr16) -> EfiResult<&mut Self> {
+        let len = Self::file_path_node_len(path) - HEADER_LEN;
+        let node = self.reserve(MEDIA_TYPE, FILE_PATH_SUB_TYPE, len)?;
+        let chars = path.chars().map(|c| c as u16).chain([0]);
+        for (dst, c) in node.chunks_exact_mut(2).zip(chars) {
+            dst.copy_from_slice(&c.to_le_bytes());
+        }
+        Ok(self)
+    }
+
+    pub fn push_vendor_media(&mut self, vendor: Guid, data: &[u8]) -> EfiResult<&mut Self> {
+        let node = self.reserve(MEDIA_TYPE, VENDOR_MEDIA_SUB_TYPE, 16 + data.len())?;
+        node[..16].copy_from_slice(&vendor.to_bytes());
+        node[16..].copy_from_slice(data);
+        Ok(self)
+    }
+
+    /// Writes the End node, and returns the path.
+    pub fn finish(self) -> EfiResult<&'a DevicePathProtocol> {
+        let end = self
+            .buf
+            .get_mut(self.len..self.len + HEADER_LEN)
+            .ok_or(StatusError::BufferTooSmall)?;
+        end.copy_from_slice(&[END_TYPE, END_ENTIRE_SUB_TYPE, HEADER_LEN as u8, 0]);
+
+        // Safety: The buffer holds a valid path, and device paths are byte-aligned
+        Ok(unsafe { &*(self.buf.as_ptr() as *const DevicePathProtocol) })
+    }
+
+    /// Writes the header of a node with `data_len` bytes of data, and returns the data.
+    fn reserve(&mut self, node_type: u8, sub_type: u8, data_len: usize) -> EfiResult<&mut [u8]> {
+        let len = HEADER_LEN + data_len;
+        let len_bytes = u16::try_from(len)
+            .map_err(|_| StatusError::InvalidParameter)?
+            .to_le_bytes();
+        // Room for the End node is always kept
+        if self.len + len + HEADER_LEN > self.buf.len() {
+            return Err(StatusError::BufferTooSmall);
+        }
+
+        let node = &mut self.buf[self.len..self.len + len];
+        node[..HEADER_LEN].copy_from_slice(&[node_type, sub_type, len_bytes[0], len_bytes[1]]);
+        self.len += len;
+        Ok(&mut node[HEADER_LEN..])
+    }
+}
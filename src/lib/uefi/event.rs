@@ -0,0 +1,129 @@
+use core::{ffi::c_void, ptr::NonNull};
+
+use bitflags::bitflags;
+
+use super::{boot_services::BootServices, status::EfiResult};
+
+bitflags! {
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct EventType: u32 {
+        const Timer                      = 0x80000000;
+        const Runtime                    = 0x40000000;
+        const NotifyWait                 = 0x00000100;
+        const NotifySignal               = 0x00000200;
+        const SignalExitBootServices     = 0x00000201;
+        const SignalVirtualAddressChange = 0x60000202;
+        const _                          = !0;
+    }
+}
+
+/// Task priority level
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Tpl(pub(crate) usize);
+
+impl Tpl {
+    pub const APPLICATION: Tpl = Tpl(4);
+    pub const CALLBACK: Tpl = Tpl(8);
+    pub const NOTIFY: Tpl = Tpl(16);
+    pub const HIGH_LEVEL: Tpl = Tpl(31);
+
+    /// Whether notification functions can be queued at this TPL
+    pub fn is_valid_notify_tpl(self) -> bool {
+        self > Self::APPLICATION && self < Self::HIGH_LEVEL
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TimerDelay {
+    /// Cancels the timer
+    Cancel,
+    /// Signals the event every `n` units of 100ns
+    Periodic(u64),
+    /// Signals the event once, in `n` units of 100ns
+    Relative(u64),
+}
+
+impl TimerDelay {
+    /// Number of 100ns units in a millisecond
+    pub const MILLISECOND: u64 = 10_000;
+    pub const SECOND: u64 = 1000 * Self::MILLISECOND;
+
+    pub(crate) fn to_raw(self) -> (u32, u64) {
+        match self {
+            TimerDelay::Cancel => (0, 0),
+            TimerDelay::Periodic(t) => (1, t),
+            TimerDelay::Relative(t) => (2, t),
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RawEvent(NonNull<c_void>);
+
+pub type EventNotifyFn = unsafe extern "efiapi" fn(event: RawEvent, context: *mut c_void);
+
+/// An event created through `BootServices`, closed once dropped. Events owned by the firmware
+/// (e.g. the `WaitForKey` event of a protocol) are never closed.
+pub struct Event {
+    raw: RawEvent,
+    boot_services: BootServices,
+    owned: bool,
+}
+
+impl Event {
+    pub(crate) fn new(raw: RawEvent, boot_services: BootServices) -> Self {
+        Self {
+            raw,
+            boot_services,
+            owned: true,
+        }
+    }
+
+    /// Wraps an event owned by the firmware, which stays valid as long as the protocol it comes
+    /// from is installed.
+    pub(crate) fn new_unowned(raw: RawEvent, boot_services: BootServices) -> Self {
+        Self {
+            raw,
+            boot_services,
+            owned: false,
+        }
+    }
+
+    pub fn raw(&self) -> RawEvent {
+        self.raw
+    }
+
+    pub fn set_timer(&self, delay: TimerDelay) -> EfiResult<()> {
+        self.boot_services.set_timer(self, delay)
+    }
+
+    /// Returns `Ok(true)` if the event is signaled, and clears its signaled state.
+    pub fn check(&self) -> EfiResult<bool> {
+        self.boot_services.check_event(self)
+    }
+
+    pub fn signal(&self) -> EfiResult<()> {
+        self.boot_services.signal_event(self)
+    }
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        if self.owned {
+            let _ = self.boot_services.close_event(self.raw);
+        }
+    }
+}
+
+/// Calls the closure passed as the notification context of an event
+pub(crate) unsafe extern "efiapi" fn closure_trampoline<F: Fn() + Sync>(
+    _event: RawEvent,
+    context: *mut c_void,
+) {
+    // Safety: The context was created from a `&'static F` in `create_callback_event`
+    let callback = unsafe { &*(context as *const F) };
+    callback();
+}
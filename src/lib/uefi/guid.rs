@@ -0,0 +1,183 @@
+use core::{fmt::Display, str::FromStr};
+
+use crate::guid;
+
+use super::{
+    config_table,
+    protocols::{
+        AcpiTableProtocol, DevicePathProtocol, DevicePathToTextProtocol, LoadFile2Protocol,
+        LoadedImageProtocol, Protocol, SimpleFileSystemProtocol, SimpleTextInputExProtocol,
+        SimpleTextInputProtocol, SimpleTextOutputProtocol,
+    },
+    runtime_services::GLOBAL_VARIABLE,
+};
+
+/// A GUID, stored as in memory: the first three fields are little-endian.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Guid([u8; 16]);
+
+/// Length of `aabbccdd-eeff-gghh-iijj-kkllmmnnoopp`
+const GUID_STR_LEN: usize = 36;
+/// Offset of the first hex digit of every byte, in memory order
+const BYTE_OFFSETS: [usize; 16] = [6, 4, 2, 0, 11, 9, 16, 14, 19, 21, 24, 26, 28, 30, 32, 34];
+
+impl Guid {
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn to_bytes(self) -> [u8; 16] {
+        self.0
+    }
+
+    /// Parses the canonical `aabbccdd-eeff-gghh-iijj-kkllmmnnoopp` form (case insensitive).
+    pub const fn parse(s: &str) -> Option<Self> {
+        let s = s.as_bytes();
+        if s.len() != GUID_STR_LEN
+            || s[8] != b'-'
+            || s[13] != b'-'
+            || s[18] != b'-'
+            || s[23] != b'-'
+        {
+            return None;
+        }
+
+        let mut bytes = [0; 16];
+        let mut i = 0;
+        while i < 16 {
+            let offset = BYTE_OFFSETS[i];
+            let (Some(high), Some(low)) = (hex_digit(s[offset]), hex_digit(s[offset + 1])) else {
+                return None;
+            };
+            bytes[i] = high << 4 | low;
+            i += 1;
+        }
+        Some(Self(bytes))
+    }
+
+    /// Name of the protocol, table or partition type identified by this GUID, if well-known
+    pub fn name(&self) -> Option<&'static str> {
+        KNOWN_GUIDS
+            .iter()
+            .find(|(guid, _)| guid == self)
+            .map(|(_, name)| *name)
+    }
+
+    /// Every well-known GUID, along with its name
+    pub fn known() -> impl Iterator<Item = (Guid, &'static str)> {
+        KNOWN_GUIDS.iter().copied()
+    }
+}
+
+const fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+impl FromStr for Guid {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).ok_or(())
+    }
+}
+
+impl Display for Guid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let b = &self.0;
+        write!(
+            f,
+            "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-",
+            b[3], b[2], b[1], b[0], b[5], b[4], b[7], b[6], b[8], b[9]
+        )?;
+        b[10..].iter().try_for_each(|c| write!(f, "{:02X}", c))
+    }
+}
+
+impl core::fmt::Debug for Guid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{} ({})", self, name),
+            None => write!(f, "{}", self),
+        }
+    }
+}
+
+/// Builds the `(guid, name)` table, one `name => guid` per line
+macro_rules! known_guids {
+    ($($name:literal => $guid:expr,)*) => {
+        &[$(($guid, $name)),*]
+    };
+}
+
+/// Names of well-known GUIDs, used for diagnostics
+static KNOWN_GUIDS: &[(Guid, &str)] = known_guids! {
+    // Protocols bound by this crate
+    "LoadedImage" => LoadedImageProtocol::GUID,
+    "SimpleFileSystem" => SimpleFileSystemProtocol::GUID,
+    "SimpleTextInput" => SimpleTextInputProtocol::GUID,
+    "SimpleTextInputEx" => SimpleTextInputExProtocol::GUID,
+    "SimpleTextOutput" => SimpleTextOutputProtocol::GUID,
+    "AcpiTable" => AcpiTableProtocol::GUID,
+    "DevicePath" => DevicePathProtocol::GUID,
+    "DevicePathToText" => DevicePathToTextProtocol::GUID,
+    "LoadFile2" => LoadFile2Protocol::GUID,
+    // Other protocols
+    "LoadedImageDevicePath" => guid!("BC62157E-3E33-4FEC-9920-2D3B36D750DF"),
+    "BlockIo" => guid!("964E5B21-6459-11D2-8E39-00A0C969723B"),
+    "BlockIo2" => guid!("A77B2472-E282-4E9F-A245-C2C0E27BBCC1"),
+    "DiskIo" => guid!("CE345171-BA0B-11D2-8E4F-00A0C969723B"),
+    "DiskIo2" => guid!("151C8EAE-7F2C-472C-9E54-9828194F6A88"),
+    "AbsolutePointer" => guid!("8D59D32B-C655-4AE9-9B15-F25904992A43"),
+    "SimplePointer" => guid!("31878C87-0B75-11D5-9A4F-0090273FC14D"),
+    "GraphicsOutput" => guid!("9042A9DE-23DC-4A38-96FB-7ADED080516A"),
+    "EdidActive" => guid!("BD8C1056-9F36-44EC-92A8-A6337F817986"),
+    "EdidDiscovered" => guid!("1C0C34F6-D380-41FA-A049-8AD06C1A66AA"),
+    "LoadFile" => guid!("56EC3091-954C-11D2-8E3F-00A0C969723B"),
+    "DriverBinding" => guid!("18A031AB-B443-4D1A-A5C0-0C09261E9F71"),
+    "ComponentName2" => guid!("6A7A5CFF-E8D9-4F70-BADA-75AB3025CE14"),
+    "PciIo" => guid!("4CF5B200-68B8-4CA5-9EEC-B23E3F50029A"),
+    "PciRootBridgeIo" => guid!("2F707EBB-4A1A-11D4-9A38-0090273FC14D"),
+    "UsbIo" => guid!("2B2F68D6-0CD2-44CF-8E8B-BBA20B1B5B75"),
+    "Usb2Hc" => guid!("3E745226-9818-45B6-A2AC-D7CD0E8BA2BC"),
+    "SimpleNetwork" => guid!("A19832B9-AC25-11D3-9A2D-0090273FC14D"),
+    "DeferredImageLoad" => guid!("E18541CD-F755-4F73-928D-643C8A79B229"),
+    "HiiString" => guid!("0FD96974-23AA-4CDC-B9CB-98D17750322A"),
+    "ShellParameters" => guid!("752F3136-4E16-4FDC-A22A-E5F46812F4CA"),
+    "Shell" => guid!("6302D008-7F9B-4F30-87AC-60C9FEF5DA4E"),
+    "ImageSecurityDatabase" => guid!("D719B2CB-3D3A-4596-A3BC-DAD00E67656F"),
+    "MemoryAttribute" => guid!("F4560CF6-40EC-4B4A-A192-BF1D57D0B189"),
+    "BusSpecificDriverOverride" => guid!("3BC1B285-8A15-4A82-AABF-4D7D13FB3265"),
+    "DriverDiagnostics2" => guid!("0784924F-E296-11D4-9A49-0090273FC14D"),
+    "DriverSupportedEfiVersion" => guid!("5C198761-16A8-4E69-972C-89D67954F81D"),
+    "Rng" => guid!("3152BCA5-EADE-433D-862E-C01CDC291F44"),
+    "Tcg2" => guid!("607F766C-7455-42BE-930B-E4D76DB2720F"),
+    // Configuration tables
+    "AcpiTable (1.0)" => config_table::ACPI_TABLE,
+    "AcpiTable (2.0)" => config_table::ACPI_20_TABLE,
+    "SmbiosTable" => config_table::SMBIOS_TABLE,
+    "Smbios3Table" => config_table::SMBIOS3_TABLE,
+    "DeviceTree" => config_table::DEVICE_TREE_TABLE,
+    "MemoryAttributesTable" => config_table::MEMORY_ATTRIBUTES_TABLE,
+    "DebugImageInfoTable" => config_table::DEBUG_IMAGE_INFO_TABLE,
+    "EfiSystemResourceTable" => guid!("B122A263-3661-4F68-9929-78F8B0D62180"),
+    "RuntimePropertiesTable" => guid!("EB66918A-7EEF-402A-842E-931D21C38AE9"),
+    // Vendor device paths
+    "LinuxInitrdMedia" => guid!("5568E427-68FC-4F3D-AC74-CA555231CC68"),
+    // Variable vendors
+    "GlobalVariable" => GLOBAL_VARIABLE,
+    // GPT partition types
+    "EfiSystemPartition" => guid!("C12A7328-F81F-11D2-BA4B-00A0C93EC93B"),
+    "BiosBootPartition" => guid!("21686148-6449-6E6F-744E-656564454649"),
+    "MicrosoftBasicData" => guid!("EBD0A0A2-B9E5-4433-87C0-68B6B72699C7"),
+    "MicrosoftReserved" => guid!("E3C9E316-0B5C-4DB8-817D-F92DF00215AE"),
+    "LinuxFilesystem" => guid!("0FC63DAF-8483-4772-8E79-3D69D8477DE4"),
+    "LinuxSwap" => guid!("0657FD6D-A4AB-43C4-84E5-0933C84B4F4F"),
+    "LinuxLvm" => guid!("E6D6D379-F507-44C2-A23C-238F2A3DF928"),
+    "LinuxRootX86_64" => guid!("4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709"),
+};
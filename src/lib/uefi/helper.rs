@@ -5,11 +5,19 @@ use core::{
     sync::atomic::{AtomicPtr, Ordering},
 };
 
-use crate::println;
-
-use super::{boot_services::BootServices, protocols::Output, status::EfiResult, SystemTable};
+use crate::{cstr16, println};
+
+use super::{
+    boot_services::BootServices,
+    event::Tpl,
+    protocols::Output,
+    status::{EfiResult, StatusError},
+    sync::TplMutex,
+    Handle, MemoryType, SystemTable,
+};
 
 pub static _ST: AtomicPtr<SystemTable> = AtomicPtr::new(ptr::null_mut());
+static IMAGE_HANDLE: AtomicPtr<*mut c_void> = AtomicPtr::new(ptr::null_mut());
 
 #[panic_handler]
 fn _panic_handler(panic_info: &core::panic::PanicInfo) -> ! {
@@ -25,6 +33,14 @@ fn _panic_handler(panic_info: &core::panic::PanicInfo) -> ! {
         //     println!("panic occurred");
         // }
     }
+    // Give the firmware a chance to move on to the next boot option
+    if let (Some(st), Some(image)) = (_get_st_safe(), image_handle()) {
+        st.boot_services().exit(
+            image,
+            StatusError::Aborted.into(),
+            Some(cstr16!("PUB: panic")),
+        );
+    }
     loop {}
 }
 
@@ -32,6 +48,21 @@ pub fn register_services(st: &SystemTable) {
     _ST.store(st as *const _ as *mut _, Ordering::Relaxed);
 }
 
+/// Registers the handle of our image, used as the agent when opening protocols.
+pub fn register_image_handle(handle: Handle) {
+    IMAGE_HANDLE.store(handle.as_ptr(), Ordering::Relaxed);
+}
+
+pub fn image_handle() -> Option<Handle> {
+    Handle::from_ptr(IMAGE_HANDLE.load(Ordering::Relaxed))
+}
+
+/// Forgets the registered system table, so that nothing tries to print to the console once boot
+/// services are exited.
+pub fn unregister_services() {
+    _ST.store(ptr::null_mut(), Ordering::Relaxed);
+}
+
 /// # Safety
 /// None, will panick if _ST hasn't been set to a valid SystemTable
 pub unsafe fn _get_st_panicking<'a>() -> &'a mut SystemTable {
@@ -48,7 +79,12 @@ pub fn _st_is_set() -> bool {
     _get_st_safe().is_some()
 }
 
+/// Serializes console output, which event notifications may use too
+static CONSOLE_LOCK: TplMutex<()> = TplMutex::new(Tpl::NOTIFY, ());
+
 pub fn _print(args: fmt::Arguments, stdout: &mut Output, newline: bool) {
+    // Nested prints (a panic while printing) go through unlocked, rather than not at all
+    let _lock = _get_st_safe().and_then(|st| CONSOLE_LOCK.try_lock(st.boot_services()));
     if newline {
         stdout.write_fmt(format_args!("{}\r\n", args))
     } else {
@@ -80,6 +116,24 @@ impl<T> AllocatedPool<T> {
             slice_size: None,
         })
     }
+
+    /// Allocates a pool and moves `value` into it, without going through a reference to the
+    /// uninitialized memory.
+    pub fn try_new_with(boot_services: BootServices, value: T) -> EfiResult<Self> {
+        let pool = Self::try_new(boot_services)?;
+        // Safety: The pool is large enough for a `T`, and pools are 8-byte aligned
+        unsafe { ptr::write(pool.ptr as *mut T, value) };
+        Ok(pool)
+    }
+}
+
+impl<T: ?Sized> AllocatedPool<T> {
+    /// Consumes the wrapper without freeing the pool, and returns a pointer to it.
+    pub fn leak(self) -> *mut c_void {
+        let ptr = self.ptr;
+        core::mem::forget(self);
+        ptr
+    }
 }
 
 impl<T> AsRef<T> for AllocatedPool<T> {
@@ -96,8 +150,18 @@ impl<T> AsMut<T> for AllocatedPool<T> {
 
 impl<T> AllocatedPool<[T]> {
     pub fn try_new(boot_services: BootServices, len: usize) -> EfiResult<Self> {
+        Self::try_new_with_mem_type(boot_services, MemoryType::EfiLoaderData, len)
+    }
+
+    /// Allocates the pool as `mem_type` memory, for data which must outlive PUB (e.g. ACPI
+    /// tables, which belong in `EfiACPIReclaimMemory`).
+    pub fn try_new_with_mem_type(
+        boot_services: BootServices,
+        mem_type: MemoryType,
+        len: usize,
+    ) -> EfiResult<Self> {
         let i = size_of::<T>();
-        let ptr = boot_services.allocate_pool(i * len)?;
+        let ptr = boot_services.allocate_pool_with_mem_type(mem_type, i * len)?;
 
         Ok(Self {
             _marker: core::marker::PhantomData,
@@ -108,6 +172,27 @@ impl<T> AllocatedPool<[T]> {
     }
 }
 
+impl<T> AllocatedPool<[T]> {
+    /// Takes ownership of a pool containing `len` elements, which was allocated by the firmware
+    /// on our behalf.
+    ///
+    /// # Safety
+    /// `ptr` must be a non-null pointer to a pool allocated with `AllocatePool`, containing `len`
+    /// initialized elements.
+    pub(crate) unsafe fn from_raw_parts(
+        boot_services: BootServices,
+        ptr: *mut T,
+        len: usize,
+    ) -> Self {
+        Self {
+            _marker: core::marker::PhantomData,
+            boot_services,
+            ptr: ptr as *mut c_void,
+            slice_size: Some(len),
+        }
+    }
+}
+
 impl<T> AsRef<[T]> for AllocatedPool<[T]> {
     fn as_ref(&self) -> &[T] {
         // Safety: The size of the slice is known, we expect it to have been initialized with
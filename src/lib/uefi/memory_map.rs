@@ -0,0 +1,179 @@
+use core::slice;
+
+use bitflags::bitflags;
+
+use super::{helper::AllocatedPool, PhysicalAddress, VirtualAddress};
+
+pub const PAGE_SIZE: u64 = 0x1000;
+
+bitflags! {
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+    pub struct MemoryAttribute: u64 {
+        const Uncacheable       = 0x0000000000000001;
+        const WriteCombining    = 0x0000000000000002;
+        const WriteThrough      = 0x0000000000000004;
+        const WriteBack         = 0x0000000000000008;
+        const UncacheableExport = 0x0000000000000010;
+        const WriteProtect      = 0x0000000000001000;
+        const ReadProtect       = 0x0000000000002000;
+        const ExecuteProtect    = 0x0000000000004000;
+        const NonVolatile       = 0x0000000000008000;
+        const MoreReliable      = 0x0000000000010000;
+        const ReadOnly          = 0x0000000000020000;
+        const SpecificPurpose   = 0x0000000000040000;
+        const CpuCrypto         = 0x0000000000080000;
+        const Runtime           = 0x8000000000000000;
+        const _                 = !0;
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryDescriptor {
+    /// Raw `EFI_MEMORY_TYPE`, can contain OEM/OS-defined values
+    pub memory_type: u32,
+    pub physical_start: PhysicalAddress,
+    pub virtual_start: VirtualAddress,
+    pub number_of_pages: u64,
+    pub attribute: MemoryAttribute,
+}
+
+impl MemoryDescriptor {
+    pub fn is_runtime(&self) -> bool {
+        self.attribute.contains(MemoryAttribute::Runtime)
+    }
+
+    pub fn size(&self) -> u64 {
+        self.number_of_pages * PAGE_SIZE
+    }
+}
+
+/// A snapshot of the firmware memory map, stored in a pool allocated by `BootServices`.
+///
+/// The descriptors are stored with a stride of `descriptor_size`, which can be bigger than
+/// `size_of::<MemoryDescriptor>()`.
+pub struct MemoryMap {
+    buffer: AllocatedPool<[u8]>,
+    map_size: usize,
+    map_key: usize,
+    descriptor_size: usize,
+    descriptor_version: u32,
+}
+
+impl MemoryMap {
+    pub(crate) fn new(
+        buffer: AllocatedPool<[u8]>,
+        map_size: usize,
+        map_key: usize,
+        descriptor_size: usize,
+        descriptor_version: u32,
+    ) -> Self {
+        Self {
+            buffer,
+            map_size,
+            map_key,
+            descriptor_size,
+            descriptor_version,
+        }
+    }
+
+    pub(crate) fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer.as_mut()
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        map_size: usize,
+        map_key: usize,
+        descriptor_size: usize,
+        descriptor_version: u32,
+    ) {
+        self.map_size = map_size;
+        self.map_key = map_key;
+        self.descriptor_size = descriptor_size;
+        self.descriptor_version = descriptor_version;
+    }
+
+    pub fn key(&self) -> usize {
+        self.map_key
+    }
+
+    /// Size of the memory map in bytes
+    pub fn size(&self) -> usize {
+        self.map_size
+    }
+
+    pub fn descriptor_size(&self) -> usize {
+        self.descriptor_size
+    }
+
+    pub fn descriptor_version(&self) -> u32 {
+        self.descriptor_version
+    }
+
+    pub fn len(&self) -> usize {
+        self.map_size / self.descriptor_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_ptr(&self) -> *const MemoryDescriptor {
+        self.buffer.as_ref().as_ptr() as *const MemoryDescriptor
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut MemoryDescriptor {
+        self.buffer.as_mut().as_mut_ptr() as *mut MemoryDescriptor
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MemoryDescriptor> {
+        let buf = &self.buffer.as_ref()[..self.map_size];
+        // Safety: The firmware filled the buffer with `len` descriptors of `descriptor_size` bytes
+        buf.chunks_exact(self.descriptor_size)
+            .map(|d| unsafe { &*(d.as_ptr() as *const MemoryDescriptor) })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut MemoryDescriptor> {
+        let descriptor_size = self.descriptor_size;
+        let buf = &mut self.buffer.as_mut()[..self.map_size];
+        buf.chunks_exact_mut(descriptor_size)
+            .map(|d| unsafe { &mut *(d.as_mut_ptr() as *mut MemoryDescriptor) })
+    }
+
+    /// Size of the underlying buffer, in bytes
+    pub fn capacity(&self) -> usize {
+        self.buffer.as_ref().len()
+    }
+
+    /// Builds a new memory map containing only the runtime descriptors of this map, which is the
+    /// format expected by `SetVirtualAddressMap`. `buffer` must be at least `self.size()` bytes.
+    /// Does not allocate memory, so it can be used after exiting boot services.
+    pub fn runtime_descriptors(&self, mut buffer: AllocatedPool<[u8]>) -> MemoryMap {
+        let out = buffer.as_mut();
+        let mut written = 0;
+        for desc in self.iter().filter(|d| d.is_runtime()) {
+            // Safety: The descriptor is `descriptor_size` bytes long
+            let raw = unsafe {
+                slice::from_raw_parts(desc as *const _ as *const u8, self.descriptor_size)
+            };
+            out[written..written + self.descriptor_size].copy_from_slice(raw);
+            written += self.descriptor_size;
+        }
+
+        MemoryMap::new(
+            buffer,
+            written,
+            self.map_key,
+            self.descriptor_size,
+            self.descriptor_version,
+        )
+    }
+
+    /// Gives up ownership of the underlying pool, which will not be freed. Required once boot
+    /// services are exited, since the pool can no longer be freed at that point.
+    pub fn leak(self) -> *mut u8 {
+        self.buffer.leak() as *mut u8
+    }
+}
@@ -1,39 +1,150 @@
+mod acpi;
 mod console;
+mod device_path;
+mod load_file;
 mod loaded_image;
 mod media;
 
+use core::{
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+pub use acpi::*;
+use bitflags::bitflags;
 pub use console::*;
+pub use device_path::*;
+pub use load_file::*;
 pub use loaded_image::*;
 pub use media::*;
 
-use super::{boot_services::BootServices, status::StatusError, Guid, Handle};
+use super::{
+    boot_services::{BootServices, HandleBuffer, SearchType},
+    status::{EfiResult, StatusError},
+    Guid, Handle,
+};
 
+bitflags! {
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct OpenProtocolAttributes: u32 {
+        const ByHandleProtocol  = 0x00000001;
+        const GetProtocol       = 0x00000002;
+        const TestProtocol      = 0x00000004;
+        const ByChildController = 0x00000008;
+        const ByDriver          = 0x00000010;
+        const Exclusive         = 0x00000020;
+        const _                 = !0;
+    }
+}
+
+#[derive(Debug)]
 pub enum ProtocolLocateError {
     Unsupported,
     Error(StatusError),
 }
 
+impl From<ProtocolLocateError> for StatusError {
+    fn from(value: ProtocolLocateError) -> Self {
+        match value {
+            ProtocolLocateError::Unsupported => StatusError::Unsupported,
+            ProtocolLocateError::Error(e) => e,
+        }
+    }
+}
+
 pub trait RawProtocol: Sized {
     const GUID: Guid;
+}
 
-    fn try_locate_protocol(
+pub trait Protocol: Sized {
+    const GUID: Guid;
+
+    /// Opens the protocol on `handle`, the same way the deprecated `HandleProtocol` would.
+    fn open(
+        handle: Handle,
         boot_services: &BootServices,
+    ) -> Result<ScopedProtocol<'_, Self>, ProtocolLocateError>;
+
+    /// Opens the protocol on `handle`, with exclusive access. Fails if someone else (including a
+    /// driver) already opened it exclusively. Only exclusive access hands out `&mut` interfaces.
+    fn open_exclusive(
         handle: Handle,
-    ) -> Result<*const Self, ProtocolLocateError> {
-        let res = boot_services.generic_handle_protocol(handle, &Self::GUID);
-        let void_interface = match res {
-            Ok(Some(x)) => x,
-            Ok(None) => return Err(ProtocolLocateError::Unsupported),
-            Err(e) => return Err(ProtocolLocateError::Error(e)),
-        };
+        boot_services: &BootServices,
+    ) -> Result<ExclusiveProtocol<'_, Self>, ProtocolLocateError>;
 
-        Ok(void_interface as *const Self)
+    /// Returns every handle supporting the protocol. Returns `Err(NotFound)` if there is none.
+    fn find_handles(boot_services: &BootServices) -> EfiResult<HandleBuffer> {
+        boot_services.locate_handle_buffer(SearchType::ByProtocol(&Self::GUID))
     }
 }
 
-pub trait Protocol {
-    fn try_locate(
+/// A protocol interface opened with `OpenProtocol`, closed once dropped. The interface can't
+/// outlive the `BootServices` it was opened with.
+///
+/// The same interface can be opened several times, so it is only shared. `CloseProtocol` closes
+/// every open of the interface by PUB at once: dropping a guard also closes the interface for
+/// the other guards on the same handle, which the firmware may then uninstall.
+pub struct ScopedProtocol<'bs, P: Protocol> {
+    interface: NonNull<P>,
+    handle: Handle,
+    agent: Handle,
+    boot_services: &'bs BootServices,
+}
+
+impl<'bs, P: Protocol> ScopedProtocol<'bs, P> {
+    pub(crate) fn new(
+        interface: NonNull<P>,
         handle: Handle,
-        boot_services: &BootServices,
-    ) -> Result<&Self, ProtocolLocateError>;
+        agent: Handle,
+        boot_services: &'bs BootServices,
+    ) -> Self {
+        Self {
+            interface,
+            handle,
+            agent,
+            boot_services,
+        }
+    }
+
+    /// Handle the protocol was opened on
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+}
+
+impl<P: Protocol> Deref for ScopedProtocol<'_, P> {
+    type Target = P;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The interface stays valid until the protocol is closed
+        unsafe { self.interface.as_ref() }
+    }
+}
+
+/// A protocol interface opened with exclusive access, see `Protocol::open_exclusive`
+pub struct ExclusiveProtocol<'bs, P: Protocol>(pub(crate) ScopedProtocol<'bs, P>);
+
+impl<P: Protocol> Deref for ExclusiveProtocol<'_, P> {
+    type Target = P;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<P: Protocol> DerefMut for ExclusiveProtocol<'_, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: The interface stays valid until the protocol is closed, and the exclusive open
+        // fails while it's opened exclusively elsewhere
+        unsafe { self.0.interface.as_mut() }
+    }
+}
+
+impl<P: Protocol> Drop for ScopedProtocol<'_, P> {
+    fn drop(&mut self) {
+        let _ = self
+            .boot_services
+            .close_protocol(self.handle, &P::GUID, self.agent, None);
+    }
 }
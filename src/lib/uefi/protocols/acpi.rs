@@ -0,0 +1,57 @@
+use core::ffi::c_void;
+
+use uefi_macros::Protocol;
+
+use crate::{
+    guid,
+    uefi::{
+        status::{EfiResult, Status},
+        Guid,
+    },
+};
+
+use super::RawProtocol;
+
+/// Installs ACPI tables. The firmware copies the table, fixes its checksum and updates the
+/// XSDT/RSDT (and the FADT, for a DSDT or FACS).
+#[repr(transparent)]
+#[derive(Protocol)]
+pub struct AcpiTableProtocol(RawAcpiTableProtocol);
+
+impl AcpiTableProtocol {
+    /// Installs a copy of `table`, returns the key used to uninstall it.
+    pub fn install_acpi_table(&self, table: &[u8]) -> EfiResult<usize> {
+        let mut key = 0;
+        // Safety: The firmware only reads `table.len()` bytes, and copies them
+        unsafe {
+            (self.0.install_acpi_table)(
+                &self.0,
+                table.as_ptr() as *const c_void,
+                table.len(),
+                &mut key,
+            )
+        }
+        .to_result()?;
+
+        Ok(key)
+    }
+
+    pub fn uninstall_acpi_table(&self, key: usize) -> EfiResult<()> {
+        unsafe { (self.0.uninstall_acpi_table)(&self.0, key) }.to_result()
+    }
+}
+
+#[repr(C)]
+struct RawAcpiTableProtocol {
+    install_acpi_table: unsafe extern "efiapi" fn(
+        this: *const Self,
+        acpi_table_buffer: *const c_void,
+        acpi_table_size: usize,
+        table_key: *mut usize,
+    ) -> Status,
+    uninstall_acpi_table: unsafe extern "efiapi" fn(this: *const Self, table_key: usize) -> Status,
+}
+
+impl RawProtocol for RawAcpiTableProtocol {
+    const GUID: Guid = guid!("FFE06BDD-6107-46A6-7BB2-5A9C7EC5275C");
+}
@@ -1,19 +1,444 @@
-use core::fmt::{self, Write};
+use core::{
+    ffi::c_void,
+    fmt::{self, Write},
+    ptr::{self, NonNull},
+};
+
+use bitflags::bitflags;
+use uefi_macros::Protocol;
 
-use crate::uefi::{
-    status::{EfiResult, Status},
-    string::CStr16,
+use crate::{
+    guid,
+    uefi::{
+        boot_services::BootServices,
+        event::{Event, RawEvent},
+        status::{EfiResult, Status, StatusError},
+        string::CStr16,
+        Guid,
+    },
 };
 
+use super::RawProtocol;
+
+pub type Input = SimpleTextInputProtocol;
 pub type Output = SimpleTextOutputProtocol;
 
 #[repr(transparent)]
+#[derive(Protocol)]
+pub struct SimpleTextInputProtocol(RawSimpleTextInputProtocol);
+
+impl SimpleTextInputProtocol {
+    /// Resets the input device, discarding pending keystrokes.
+    pub fn reset(&mut self, extended_verification: bool) -> EfiResult<()> {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification.into()) }.to_result()
+    }
+
+    /// Reads the next keystroke, `None` if there is none pending.
+    pub fn read_key_stroke(&mut self) -> EfiResult<Option<Key>> {
+        let mut key = InputKey::default();
+        match unsafe { (self.0.read_key_stroke)(&mut self.0, &mut key) }.to_result() {
+            Ok(()) => Ok(Some(key.into())),
+            Err(StatusError::NotReady) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Event signaled when a keystroke is pending, owned by the firmware.
+    pub fn wait_for_key(&self, boot_services: BootServices) -> Event {
+        Event::new_unowned(self.0.wait_for_key, boot_services)
+    }
+
+    /// Blocks until a key is pressed. Can only be called at `Tpl::APPLICATION`.
+    pub fn read_key(&mut self, boot_services: BootServices) -> EfiResult<Key> {
+        let event = self.wait_for_key(boot_services);
+        loop {
+            // The event may be signaled by a keystroke the firmware then drops (e.g. a lone
+            // modifier), so wait again if there is nothing to read
+            if let Some(key) = self.read_key_stroke()? {
+                return Ok(key);
+            }
+            boot_services.wait_for_any(&[&event])?;
+        }
+    }
+}
+
+#[repr(C)]
+struct RawSimpleTextInputProtocol {
+    reset: unsafe extern "efiapi" fn(this: *mut Self, extended_verification: u8) -> Status,
+    read_key_stroke: unsafe extern "efiapi" fn(this: *mut Self, key: *mut InputKey) -> Status,
+    wait_for_key: RawEvent,
+}
+
+impl RawProtocol for RawSimpleTextInputProtocol {
+    const GUID: Guid = guid!("387477C1-69C7-11D2-8E39-00A0C969723B");
+}
+
+/// `EFI_INPUT_KEY`, either a scan code or a UCS-2 character is set
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct InputKey {
+    pub scan_code: u16,
+    pub unicode_char: u16,
+}
+
+/// A key press, as read from the console
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Key {
+    /// A printable character
+    Char(char),
+    Enter,
+    Backspace,
+    Tab,
+    Up,
+    Down,
+    Right,
+    Left,
+    Home,
+    End,
+    Insert,
+    Delete,
+    PageUp,
+    PageDown,
+    /// Function keys, F1 to F12
+    Function(u8),
+    Escape,
+    /// Other scan codes and control characters
+    Unknown(InputKey),
+}
+
+impl From<InputKey> for Key {
+    fn from(key: InputKey) -> Self {
+        match (key.scan_code, key.unicode_char) {
+            (0x00, 0x0D) => Key::Enter,
+            (0x00, 0x08) => Key::Backspace,
+            (0x00, 0x09) => Key::Tab,
+            (0x00, c) => match char::from_u32(c as u32) {
+                Some(c) if !c.is_control() => Key::Char(c),
+                _ => Key::Unknown(key),
+            },
+            (0x01, _) => Key::Up,
+            (0x02, _) => Key::Down,
+            (0x03, _) => Key::Right,
+            (0x04, _) => Key::Left,
+            (0x05, _) => Key::Home,
+            (0x06, _) => Key::End,
+            (0x07, _) => Key::Insert,
+            (0x08, _) => Key::Delete,
+            (0x09, _) => Key::PageUp,
+            (0x0A, _) => Key::PageDown,
+            (code @ 0x0B..=0x14, _) => Key::Function((code - 0x0A) as u8),
+            (0x15, _) => Key::Function(11),
+            (0x16, _) => Key::Function(12),
+            (0x17, _) => Key::Escape,
+            _ => Key::Unknown(key),
+        }
+    }
+}
+
+bitflags! {
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+    pub struct ShiftState: u32 {
+        const RightShift = 0x00000001;
+        const LeftShift  = 0x00000002;
+        const RightCtrl  = 0x00000004;
+        const LeftCtrl   = 0x00000008;
+        const RightAlt   = 0x00000010;
+        const LeftAlt    = 0x00000020;
+        const RightLogo  = 0x00000040;
+        const LeftLogo   = 0x00000080;
+        const Menu       = 0x00000100;
+        const SysReq     = 0x00000200;
+        /// The other bits are only meaningful if this one is set
+        const Valid      = 0x80000000;
+        const _          = !0;
+    }
+
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+    pub struct ToggleState: u8 {
+        const ScrollLock = 0x01;
+        const NumLock    = 0x02;
+        const CapsLock   = 0x04;
+        /// Partial keystrokes (e.g. a lone Shift) are reported
+        const Exposed    = 0x40;
+        /// The other bits are only meaningful if this one is set
+        const Valid      = 0x80;
+        const _          = !0;
+    }
+}
+
+/// `EFI_KEY_DATA`, a keystroke along with the state of the modifiers
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct KeyData {
+    pub key: InputKey,
+    pub shift_state: ShiftState,
+    pub toggle_state: ToggleState,
+}
+
+impl KeyData {
+    pub fn key(&self) -> Key {
+        self.key.into()
+    }
+}
+
+/// Called with the keystroke which matched a `register_key_notify` registration
+pub type KeyNotifyFn = unsafe extern "efiapi" fn(key: *const KeyData) -> Status;
+
+/// Handle of a `register_key_notify` registration
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyNotifyHandle(NonNull<c_void>);
+
+#[repr(transparent)]
+#[derive(Protocol)]
+pub struct SimpleTextInputExProtocol(RawSimpleTextInputExProtocol);
+
+impl SimpleTextInputExProtocol {
+    /// Resets the input device, discarding pending keystrokes.
+    pub fn reset(&mut self, extended_verification: bool) -> EfiResult<()> {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification.into()) }.to_result()
+    }
+
+    /// Reads the next keystroke and the modifiers state, `None` if there is none pending. With
+    /// `ToggleState::Exposed` set, the key may be empty (only a modifier was pressed).
+    pub fn read_key_stroke(&mut self) -> EfiResult<Option<KeyData>> {
+        let mut data = KeyData::default();
+        match unsafe { (self.0.read_key_stroke_ex)(&mut self.0, &mut data) }.to_result() {
+            Ok(()) => Ok(Some(data)),
+            Err(StatusError::NotReady) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Event signaled when a keystroke is pending, owned by the firmware.
+    pub fn wait_for_key(&self, boot_services: BootServices) -> Event {
+        Event::new_unowned(self.0.wait_for_key_ex, boot_services)
+    }
+
+    /// Blocks until a key is pressed. Can only be called at `Tpl::APPLICATION`.
+    pub fn read_key(&mut self, boot_services: BootServices) -> EfiResult<KeyData> {
+        let event = self.wait_for_key(boot_services);
+        loop {
+            if let Some(data) = self.read_key_stroke()? {
+                return Ok(data);
+            }
+            boot_services.wait_for_any(&[&event])?;
+        }
+    }
+
+    /// Sets the state of the Caps/Num/Scroll lock keys (and their LEDs). `ToggleState::Valid`
+    /// needs to be set.
+    pub fn set_state(&mut self, state: ToggleState) -> EfiResult<()> {
+        unsafe { (self.0.set_state)(&mut self.0, &state) }.to_result()
+    }
+
+    /// Calls `notify` whenever `key` is pressed (with the same modifiers, if `shift_state` is
+    /// valid). `notify` runs at `Tpl::NOTIFY` or lower, in the middle of whatever PUB is doing.
+    pub fn register_key_notify(
+        &mut self,
+        key: &KeyData,
+        notify: KeyNotifyFn,
+    ) -> EfiResult<KeyNotifyHandle> {
+        let mut handle: *mut c_void = ptr::null_mut();
+        unsafe { (self.0.register_key_notify)(&mut self.0, key, notify, &mut handle) }
+            .to_result()?;
+
+        NonNull::new(handle)
+            .map(KeyNotifyHandle)
+            .ok_or(StatusError::InvalidParameter)
+    }
+
+    pub fn unregister_key_notify(&mut self, handle: KeyNotifyHandle) -> EfiResult<()> {
+        unsafe { (self.0.unregister_key_notify)(&mut self.0, handle.0.as_ptr()) }.to_result()
+    }
+}
+
+#[repr(C)]
+struct RawSimpleTextInputExProtocol {
+    reset: unsafe extern "efiapi" fn(this: *mut Self, extended_verification: u8) -> Status,
+    read_key_stroke_ex: unsafe extern "efiapi" fn(this: *mut Self, key: *mut KeyData) -> Status,
+    wait_for_key_ex: RawEvent,
+    set_state:
+        unsafe extern "efiapi" fn(this: *mut Self, toggle_state: *const ToggleState) -> Status,
+    register_key_notify: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        key: *const KeyData,
+        notify: KeyNotifyFn,
+        handle: *mut *mut c_void,
+    ) -> Status,
+    unregister_key_notify:
+        unsafe extern "efiapi" fn(this: *mut Self, handle: *mut c_void) -> Status,
+}
+
+impl RawProtocol for RawSimpleTextInputExProtocol {
+    const GUID: Guid = guid!("DD9E7534-7762-4698-8C14-F58517A625AA");
+}
+
+#[repr(transparent)]
+#[derive(Protocol)]
 pub struct SimpleTextOutputProtocol(RawSimpleTextOutputProtocol);
 
 impl SimpleTextOutputProtocol {
+    /// Resets the output device, which usually clears the screen.
+    pub fn reset(&mut self, extended_verification: bool) -> EfiResult<()> {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification) }.to_result()
+    }
+
     pub fn write(&mut self, s: &CStr16) -> EfiResult<()> {
         unsafe { (self.0.output_string)(&mut self.0, s.as_ptr()) }.to_result()
     }
+
+    /// Checks whether every character of `s` can be displayed. Fails with `Unsupported` if not.
+    pub fn test_string(&mut self, s: &CStr16) -> EfiResult<()> {
+        unsafe { (self.0.test_string)(&mut self.0, s.as_ptr()) }.to_result()
+    }
+
+    /// Current mode, attribute and cursor of the console
+    pub fn mode(&self) -> &SimpleTextOutputMode {
+        // Safety: The firmware keeps the mode up to date for as long as the protocol exists
+        unsafe { &*self.0.mode }
+    }
+
+    /// Returns the number of columns and rows of the text mode `mode`. Fails with `Unsupported`
+    /// if the mode isn't available on the current device.
+    pub fn query_mode(&mut self, mode: usize) -> EfiResult<(usize, usize)> {
+        let (mut columns, mut rows) = (0, 0);
+        unsafe { (self.0.query_mode)(&mut self.0, mode, &mut columns, &mut rows) }.to_result()?;
+        Ok((columns, rows))
+    }
+
+    /// Switches to the text mode `mode`, which clears the screen.
+    pub fn set_mode(&mut self, mode: usize) -> EfiResult<()> {
+        unsafe { (self.0.set_mode)(&mut self.0, mode) }.to_result()
+    }
+
+    /// Switches to the available text mode with the most characters, and returns its number of
+    /// columns and rows.
+    pub fn set_largest_mode(&mut self) -> EfiResult<(usize, usize)> {
+        let max_mode = self.mode().max_mode();
+        let (mode, size) = (0..max_mode)
+            .filter_map(|mode| Some((mode, self.query_mode(mode).ok()?)))
+            .max_by_key(|(_, (columns, rows))| columns * rows)
+            .ok_or(StatusError::Unsupported)?;
+
+        if mode != self.mode().mode() {
+            self.set_mode(mode)?;
+        }
+        Ok(size)
+    }
+
+    /// Sets the colors of the text written from now on. Only the first 8 colors can be used as a
+    /// background, others fail with `InvalidParameter`.
+    pub fn set_color(&mut self, foreground: Color, background: Color) -> EfiResult<()> {
+        if background as u8 > Color::LightGray as u8 {
+            return Err(StatusError::InvalidParameter);
+        }
+        let attribute = foreground as usize | (background as usize) << 4;
+        unsafe { (self.0.set_attribute)(&mut self.0, attribute) }.to_result()
+    }
+
+    /// Clears the screen with the current background color, and moves the cursor to (0, 0).
+    pub fn clear_screen(&mut self) -> EfiResult<()> {
+        unsafe { (self.0.clear_screen)(&mut self.0) }.to_result()
+    }
+
+    /// Moves the cursor, (0, 0) being the top left corner. Fails with `Unsupported` if the
+    /// position is outside of the screen.
+    pub fn set_cursor_position(&mut self, column: usize, row: usize) -> EfiResult<()> {
+        unsafe { (self.0.set_cursor_position)(&mut self.0, column, row) }.to_result()
+    }
+
+    /// Shows or hides the cursor. Fails with `Unsupported` if the device can't hide it.
+    pub fn enable_cursor(&mut self, visible: bool) -> EfiResult<()> {
+        unsafe { (self.0.enable_cursor)(&mut self.0, visible) }.to_result()
+    }
+}
+
+/// Text colors. Only the first 8 can be used as a background.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Color {
+    Black,
+    Blue,
+    Green,
+    Cyan,
+    Red,
+    Magenta,
+    Brown,
+    LightGray,
+    DarkGray,
+    LightBlue,
+    LightGreen,
+    LightCyan,
+    LightRed,
+    LightMagenta,
+    Yellow,
+    White,
+}
+
+impl Color {
+    const ALL: [Color; 16] = [
+        Color::Black,
+        Color::Blue,
+        Color::Green,
+        Color::Cyan,
+        Color::Red,
+        Color::Magenta,
+        Color::Brown,
+        Color::LightGray,
+        Color::DarkGray,
+        Color::LightBlue,
+        Color::LightGreen,
+        Color::LightCyan,
+        Color::LightRed,
+        Color::LightMagenta,
+        Color::Yellow,
+        Color::White,
+    ];
+}
+
+/// `SIMPLE_TEXT_OUTPUT_MODE`, maintained by the firmware
+#[repr(C)]
+pub struct SimpleTextOutputMode {
+    max_mode: i32,
+    mode: i32,
+    attribute: i32,
+    cursor_column: i32,
+    cursor_row: i32,
+    cursor_visible: u8,
+}
+
+impl SimpleTextOutputMode {
+    /// Number of text modes, valid modes go from 0 to `max_mode() - 1`
+    pub fn max_mode(&self) -> usize {
+        self.max_mode.max(0) as usize
+    }
+
+    pub fn mode(&self) -> usize {
+        self.mode.max(0) as usize
+    }
+
+    /// Current foreground and background colors
+    pub fn colors(&self) -> (Color, Color) {
+        let attribute = self.attribute as usize;
+        (
+            Color::ALL[attribute & 0x0F],
+            Color::ALL[(attribute >> 4) & 0x07],
+        )
+    }
+
+    /// Column and row of the cursor
+    pub fn cursor_position(&self) -> (usize, usize) {
+        (
+            self.cursor_column.max(0) as usize,
+            self.cursor_row.max(0) as usize,
+        )
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible != 0
+    }
 }
 
 impl Write for SimpleTextOutputProtocol {
@@ -72,5 +497,9 @@ pub struct RawSimpleTextOutputProtocol {
     pub set_cursor_position:
         unsafe extern "efiapi" fn(this: *mut Self, col: usize, row: usize) -> Status,
     pub enable_cursor: unsafe extern "efiapi" fn(this: *mut Self, visible: bool) -> Status,
-    mode: usize, // TODO
+    mode: *const SimpleTextOutputMode,
+}
+
+impl RawProtocol for RawSimpleTextOutputProtocol {
+    const GUID: Guid = guid!("387477C2-69C7-11D2-8E39-00A0C969723B");
 }
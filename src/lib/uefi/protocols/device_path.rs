@@ -0,0 +1,192 @@
+use core::{fmt::Display, ops::Deref};
+
+use uefi_macros::Protocol;
+
+use crate::{
+    guid,
+    uefi::{
+        boot_services::BootServices,
+        device_path::{
+            DevicePathBuilder, DevicePathNodes, HardDrive, NodeKind, END_ENTIRE_SUB_TYPE, END_TYPE,
+            HEADER_LEN, MEDIA_TYPE, VENDOR_MEDIA_SUB_TYPE,
+        },
+        helper::AllocatedPool,
+        status::EfiResult,
+        string::{CStr16, PoolCStr16},
+        Guid,
+    },
+};
+
+use super::RawProtocol;
+
+/// A device path, i.e. the first node of a list which ends with an "End Entire" node. Only the
+/// header of the first node is part of the struct, the rest follows it in memory.
+#[repr(transparent)]
+#[derive(Protocol)]
+pub struct DevicePathProtocol(RawDevicePathProtocol);
+
+impl DevicePathProtocol {
+    /// Iterates over the nodes of the path, the final End node excluded.
+    pub fn nodes(&self) -> DevicePathNodes<'_> {
+        // Safety: The path ends with an End Entire node, and lives as long as `self`
+        unsafe { DevicePathNodes::new(&self.0 as *const RawDevicePathProtocol as *const u8) }
+    }
+
+    /// The partition the path points to (or into), if any
+    pub fn hard_drive(&self) -> Option<HardDrive> {
+        self.nodes().find_map(|n| match n.kind() {
+            NodeKind::HardDrive(hd) => Some(hd),
+            _ => None,
+        })
+    }
+
+    /// Size of the path in bytes, End node included
+    pub fn size(&self) -> usize {
+        self.nodes()
+            .map(|n| HEADER_LEN + n.data.len())
+            .sum::<usize>()
+            + HEADER_LEN
+    }
+
+    /// Builds the path of the file at `path` on this device (usually a volume), by appending a
+    /// File Path media node.
+    pub fn with_file_path(
+        &self,
+        path: &CStr16,
+        boot_services: BootServices,
+    ) -> EfiResult<PoolDevicePath> {
+        let len = self.size() + DevicePathBuilder::file_path_node_len(path);
+        let mut pool = AllocatedPool::<[u8]>::try_new(boot_services, len)?;
+
+        let mut builder = DevicePathBuilder::new(pool.as_mut());
+        builder.push_path(self)?.push_file_path(path)?;
+        builder.finish()?;
+        Ok(PoolDevicePath(pool))
+    }
+}
+
+/// A device path made of a single Vendor media node, used to identify things which aren't devices
+/// (e.g. the initrd served by `LoadFile2`). Unlike `DevicePathBuilder::push_vendor_media`, it can
+/// be built at compile time, for statics.
+#[repr(C)]
+pub struct VendorMediaDevicePath {
+    header: RawDevicePathProtocol,
+    vendor: Guid,
+    end: RawDevicePathProtocol,
+}
+
+impl VendorMediaDevicePath {
+    pub const fn new(vendor: Guid) -> Self {
+        Self {
+            header: RawDevicePathProtocol {
+                node_type: MEDIA_TYPE,
+                sub_type: VENDOR_MEDIA_SUB_TYPE,
+                length: ((HEADER_LEN + size_of::<Guid>()) as u16).to_le_bytes(),
+            },
+            vendor,
+            end: RawDevicePathProtocol {
+                node_type: END_TYPE,
+                sub_type: END_ENTIRE_SUB_TYPE,
+                length: (HEADER_LEN as u16).to_le_bytes(),
+            },
+        }
+    }
+}
+
+impl Deref for VendorMediaDevicePath {
+    type Target = DevicePathProtocol;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The struct is a valid device path, with no padding since every field is
+        // byte-aligned
+        unsafe { &*(self as *const Self as *const DevicePathProtocol) }
+    }
+}
+
+/// A device path built by PUB, freed once dropped
+pub struct PoolDevicePath(AllocatedPool<[u8]>);
+
+impl Deref for PoolDevicePath {
+    type Target = DevicePathProtocol;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The pool holds a valid device path, which is byte-aligned
+        unsafe { &*(self.0.as_ref().as_ptr() as *const DevicePathProtocol) }
+    }
+}
+
+impl Display for DevicePathProtocol {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Instances are separated by a ",", which neither needs a "/" before nor after it
+        let mut after_separator = true;
+        for node in self.nodes() {
+            if !after_separator && !node.is_end_instance() {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", node)?;
+            after_separator = node.is_end_instance();
+        }
+        Ok(())
+    }
+}
+
+#[repr(C)]
+struct RawDevicePathProtocol {
+    node_type: u8,
+    sub_type: u8,
+    length: [u8; 2],
+}
+
+impl RawProtocol for RawDevicePathProtocol {
+    const GUID: Guid = guid!("09576E91-6D3F-11D2-8E39-00A0C969723B");
+}
+
+/// Converts device paths to their text form, as printed by the UEFI shell. Not every firmware
+/// provides it.
+#[repr(transparent)]
+#[derive(Protocol)]
+pub struct DevicePathToTextProtocol(RawDevicePathToTextProtocol);
+
+impl DevicePathToTextProtocol {
+    /// Converts `path` to text. `display_only` uses the shorter form meant to be read by humans.
+    /// Returns `None` if the firmware couldn't allocate the string.
+    pub fn convert_device_path_to_text(
+        &self,
+        path: &DevicePathProtocol,
+        display_only: bool,
+        boot_services: BootServices,
+    ) -> Option<PoolCStr16> {
+        // Safety: The path is valid, shortcuts are allowed since the text is only displayed
+        let ptr = unsafe {
+            (self.0.convert_device_path_to_text)(&path.0, display_only.into(), true.into())
+        };
+        if ptr.is_null() {
+            return None;
+        }
+
+        // Safety: The firmware allocated a pool containing a null-terminated string
+        unsafe {
+            let len = CStr16::from_ptr(ptr).len() + 1;
+            let pool = AllocatedPool::from_raw_parts(boot_services, ptr, len);
+            Some(PoolCStr16::new(pool))
+        }
+    }
+}
+
+#[repr(C)]
+struct RawDevicePathToTextProtocol {
+    convert_device_node_to_text: unsafe extern "efiapi" fn(
+        device_node: *const RawDevicePathProtocol,
+        display_only: u8,
+        allow_shortcuts: u8,
+    ) -> *mut u16,
+    convert_device_path_to_text: unsafe extern "efiapi" fn(
+        device_path: *const RawDevicePathProtocol,
+        display_only: u8,
+        allow_shortcuts: u8,
+    ) -> *mut u16,
+}
+
+impl RawProtocol for RawDevicePathToTextProtocol {
+    const GUID: Guid = guid!("8B843E20-8132-4852-90CC-551A4E4A7F1C");
+}
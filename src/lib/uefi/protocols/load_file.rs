@@ -0,0 +1,118 @@
+use core::{ffi::c_void, ptr};
+
+use uefi_macros::Protocol;
+
+use crate::{
+    guid,
+    uefi::{
+        status::{EfiResult, Status, StatusError},
+        Guid,
+    },
+};
+
+use super::{DevicePathProtocol, RawProtocol};
+
+/// Loads a file which isn't on a file system, identified by a device path. Unlike `LoadFile`, it
+/// is never used to load boot options.
+#[repr(transparent)]
+#[derive(Protocol)]
+pub struct LoadFile2Protocol(RawLoadFile2Protocol);
+
+impl LoadFile2Protocol {
+    /// Loads the file into `buf`, returns its size. With `None` (or a buffer too small, which
+    /// fails with `BufferTooSmall`), only the size is returned.
+    pub fn load_file(&self, path: &DevicePathProtocol, buf: Option<&mut [u8]>) -> EfiResult<usize> {
+        let (buffer, mut buffer_size) = match buf {
+            Some(buf) => (buf.as_mut_ptr() as *mut c_void, buf.len()),
+            None => (ptr::null_mut(), 0),
+        };
+        let status = unsafe {
+            (self.0.load_file)(
+                &self.0 as *const _ as *mut _,
+                path,
+                0,
+                &mut buffer_size,
+                buffer,
+            )
+        };
+
+        match status.to_result() {
+            Err(StatusError::BufferTooSmall) if buffer.is_null() => Ok(buffer_size),
+            res => res.map(|_| buffer_size),
+        }
+    }
+}
+
+#[repr(C)]
+struct RawLoadFile2Protocol {
+    load_file: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        file_path: *const DevicePathProtocol,
+        boot_policy: u8,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+    ) -> Status,
+}
+
+impl RawProtocol for RawLoadFile2Protocol {
+    const GUID: Guid = guid!("4006C0C1-FCB3-403E-996D-4A6C8724E06D");
+}
+
+/// A `LoadFile2` implementation serving a buffer, e.g. an initrd. The interface to install is
+/// `as_protocol`.
+#[repr(C)]
+pub struct BufferLoadFile2 {
+    // Must come first, the firmware hands us a pointer to it
+    protocol: RawLoadFile2Protocol,
+    buffer: *const u8,
+    len: usize,
+}
+
+impl BufferLoadFile2 {
+    /// # Safety
+    /// `buffer` must stay valid for as long as the protocol is installed.
+    pub unsafe fn new(buffer: &[u8]) -> Self {
+        Self {
+            protocol: RawLoadFile2Protocol {
+                load_file: load_buffer,
+            },
+            buffer: buffer.as_ptr(),
+            len: buffer.len(),
+        }
+    }
+
+    pub fn as_protocol(&self) -> &LoadFile2Protocol {
+        // Safety: `LoadFile2Protocol` is a transparent wrapper around the raw protocol
+        unsafe { &*(&self.protocol as *const RawLoadFile2Protocol as *const LoadFile2Protocol) }
+    }
+}
+
+unsafe extern "efiapi" fn load_buffer(
+    this: *mut RawLoadFile2Protocol,
+    _file_path: *const DevicePathProtocol,
+    boot_policy: u8,
+    buffer_size: *mut usize,
+    buffer: *mut c_void,
+) -> Status {
+    if this.is_null() || buffer_size.is_null() {
+        return StatusError::InvalidParameter.into();
+    }
+    // LoadFile2 is never used for boot options
+    if boot_policy != 0 {
+        return StatusError::Unsupported.into();
+    }
+
+    // Safety: `this` is the `protocol` field of a `BufferLoadFile2`, which comes first
+    let this = unsafe { &*(this as *const BufferLoadFile2) };
+    // Safety: Checked above, the caller gives the size of its buffer
+    let size = unsafe { &mut *buffer_size };
+    if buffer.is_null() || *size < this.len {
+        *size = this.len;
+        return StatusError::BufferTooSmall.into();
+    }
+
+    // Safety: The caller's buffer holds at least `len` bytes, ours is valid while installed
+    unsafe { ptr::copy_nonoverlapping(this.buffer, buffer as *mut u8, this.len) };
+    *size = this.len;
+    Status::SUCCESS
+}
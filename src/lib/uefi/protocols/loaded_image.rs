@@ -1,13 +1,13 @@
-use core::ffi::c_void;
+use core::{ffi::c_void, slice};
 
 use uefi_macros::Protocol;
 
 use crate::{
     guid,
-    uefi::{status::Status, Guid, Handle, MemoryType, RawSystemTable},
+    uefi::{status::Status, string::CStr16, Guid, Handle, MemoryType, RawSystemTable},
 };
 
-use super::RawProtocol;
+use super::{DevicePathProtocol, RawProtocol};
 
 #[repr(transparent)]
 #[derive(Protocol)]
@@ -17,6 +17,97 @@ impl LoadedImageProtocol {
     pub fn device(&self) -> Handle {
         self.0.device_handle
     }
+
+    /// Path of the image file, relative to `device()`. `None` if the image was loaded from memory.
+    pub fn file_path(&self) -> Option<&DevicePathProtocol> {
+        // Safety: The firmware sets a valid path, or null
+        unsafe { self.0.file_path.as_ref() }
+    }
+
+    /// Options (usually a command line) the image was started with. `None` if there are none, or
+    /// if they aren't text: firmware boot options may pass binary data.
+    pub fn load_options(&self) -> Option<LoadOptions<'_>> {
+        let size = self.0.load_options_size as usize;
+        if self.0.load_options.is_null() || size == 0 || !size.is_multiple_of(2) {
+            return None;
+        }
+        // Safety: The firmware (or the parent image) set `size` bytes of options
+        let bytes = unsafe { slice::from_raw_parts(self.0.load_options as *const u8, size) };
+        let options = LoadOptions(bytes);
+        // Control characters (a null terminator aside) mean this isn't a command line
+        let is_text = options
+            .chars()
+            .all(|c| !c.is_control() || c.is_whitespace());
+        is_text.then_some(options)
+    }
+
+    /// Sets the options (usually a command line) passed to an image which wasn't started yet.
+    ///
+    /// # Safety
+    /// `options` must stay valid until the image is done with them, which usually means until it
+    /// exits.
+    pub unsafe fn set_load_options(&mut self, options: &CStr16) {
+        self.0.load_options = options.as_ptr() as *const c_void;
+        self.0.load_options_size = ((options.len() + 1) * 2) as u32;
+    }
+}
+
+/// UCS-2 options of an image, up to the first null character. They aren't guaranteed to be
+/// aligned, so they can't be borrowed as a `CStr16`.
+#[derive(Clone, Copy)]
+pub struct LoadOptions<'a>(&'a [u8]);
+
+impl LoadOptions<'_> {
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.0
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|c| *c != 0)
+            .map(|c| char::from_u32(c as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Decodes the options into `buf`, returns `None` if they don't fit.
+    pub fn to_str<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str> {
+        let mut len = 0;
+        for c in self.chars() {
+            let dst = buf.get_mut(len..len + c.len_utf8())?;
+            c.encode_utf8(dst);
+            len += c.len_utf8();
+        }
+        // Safety: Only whole UTF-8 encoded chars were written
+        Some(unsafe { core::str::from_utf8_unchecked(&buf[..len]) })
+    }
+
+    /// Splits the options into whitespace-separated arguments, decoded into `buf`. Returns `None`
+    /// if they don't fit.
+    pub fn args<'b>(&self, buf: &'b mut [u8]) -> Option<Arguments<'b>> {
+        Some(Arguments(self.to_str(buf)?))
+    }
+}
+
+/// Whitespace-separated arguments. Whitespace between double quotes doesn't split arguments, the
+/// quotes are kept.
+pub struct Arguments<'a>(&'a str);
+
+impl<'a> Iterator for Arguments<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.0.trim_start();
+        let mut quoted = false;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+                !quoted && c.is_whitespace()
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        let (arg, rest) = rest.split_at(end);
+        self.0 = rest;
+        (!arg.is_empty()).then_some(arg)
+    }
 }
 
 #[repr(C)]
@@ -25,7 +116,7 @@ struct RawEfiLoadedImageProtocol {
     pub parent_handle: Handle,
     pub system_table: *const RawSystemTable,
     pub device_handle: Handle,
-    file_path: usize, // TODO: EFI_DEVICE_PATH_PROTOCOL
+    file_path: *const DevicePathProtocol,
     _reserved: *const c_void,
     pub load_options_size: u32,
     pub load_options: *const c_void,
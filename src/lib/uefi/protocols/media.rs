@@ -6,7 +6,7 @@ use uefi_macros::Protocol;
 use crate::{
     guid,
     uefi::{
-        status::{EfiResult, Status, StatusError},
+        status::{EfiResult, Status, StatusError, Warning},
         string::CStr16,
         Guid,
     },
@@ -54,6 +54,15 @@ impl SimpleFileSystemProtocol {
         // Safety: Assumes root is a valid pointer (checking the status above)
         unsafe { Ok(&*(root as *const FileProtocol)) }
     }
+
+    /// Reads the label of the volume, using `buf` as storage.
+    pub fn volume_label<'a>(&self, buf: &'a mut [u16]) -> EfiResult<&'a CStr16> {
+        let root = self.open_volume()?;
+        let label = root.volume_label(buf);
+        // Safety: The root directory isn't used past this point
+        let _ = unsafe { root.close() };
+        label
+    }
 }
 
 #[repr(C)]
@@ -67,6 +76,9 @@ impl RawProtocol for RawSimpleFileSystemProtocol {
     const GUID: Guid = guid!("964E5B22-6459-11D2-8E39-00A0C969723B");
 }
 
+/// `EFI_FILE_SYSTEM_VOLUME_LABEL` info type, holding only the (null-terminated) label
+pub const FILE_SYSTEM_VOLUME_LABEL_ID: Guid = guid!("DB47D7D3-FE81-11D3-9A35-0090273FC14D");
+
 #[repr(transparent)]
 pub struct FileProtocol(RawFileProtocol);
 
@@ -143,10 +155,113 @@ impl FileProtocol {
         Ok(buf_size == n)
     }
 
+    /// Reads up to `buf.len()` bytes from the file. Returns the number of bytes read, 0 means the
+    /// end of the file was reached.
+    pub fn read_bytes(&self, buf: &mut [u8]) -> EfiResult<usize> {
+        let mut buf_size = buf.len();
+        // Safety: `buf_size` is the length of the buffer
+        unsafe {
+            (self.0.read)(
+                self as *const _ as *mut _,
+                &mut buf_size as *mut _,
+                buf.as_mut_ptr() as *mut c_void,
+            )
+        }
+        .to_result()?;
+
+        Ok(buf_size)
+    }
+
+    /// Writes `buf` to the file, returns the number of bytes written.
+    pub fn write_bytes(&self, buf: &[u8]) -> EfiResult<usize> {
+        let mut buf_size = buf.len();
+        // Safety: `buf_size` is the length of the buffer, which is only read
+        unsafe {
+            (self.0.write)(
+                self as *const _ as *mut _,
+                &mut buf_size as *mut _,
+                buf.as_ptr() as *const c_void,
+            )
+        }
+        .to_result()?;
+
+        Ok(buf_size)
+    }
+
+    pub fn position(&self) -> EfiResult<u64> {
+        let mut position = 0;
+        // Safety: Assumes self is a valid reference
+        unsafe { (self.0.get_position)(self as *const _ as *mut _, &mut position) }.to_result()?;
+        Ok(position)
+    }
+
     pub fn set_position(&self, position: u64) -> EfiResult<()> {
         // Safety: Assumes self is a valid reference
         unsafe { (self.0.set_position)(self as *const _ as *mut _, position) }.to_result()
     }
+
+    /// Returns the size of the file. Moves the position to the end of the file.
+    pub fn size(&self) -> EfiResult<u64> {
+        // Seeking to u64::MAX moves to the end of the file
+        self.set_position(u64::MAX)?;
+        self.position()
+    }
+
+    pub fn flush(&self) -> EfiResult<()> {
+        // Safety: Assumes self is a valid reference
+        unsafe { (self.0.flush)(self as *const _ as *mut _) }.to_result()
+    }
+
+    /// Reads the `information_type` info of the file (or its file system) into `buf`, returns the
+    /// number of bytes written. Fails with `BufferTooSmall` if `buf` can't hold the info.
+    pub fn get_info(&self, information_type: &Guid, buf: &mut [u8]) -> EfiResult<usize> {
+        let mut buf_size = buf.len();
+        // Safety: `buf_size` is the length of the buffer
+        unsafe {
+            (self.0.get_info)(
+                self as *const _ as *mut _,
+                information_type,
+                &mut buf_size,
+                buf.as_mut_ptr() as *mut c_void,
+            )
+        }
+        .to_result()?;
+
+        Ok(buf_size)
+    }
+
+    /// Reads the label of the volume the file is on, using `buf` as storage.
+    pub fn volume_label<'a>(&self, buf: &'a mut [u16]) -> EfiResult<&'a CStr16> {
+        // Safety: A [u16] can be viewed as bytes, with twice as many elements
+        let bytes =
+            unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, buf.len() * 2) };
+        let len = self.get_info(&FILE_SYSTEM_VOLUME_LABEL_ID, bytes)? / 2;
+
+        // The firmware should null-terminate the label, but don't take its word for it
+        let end = buf[..len]
+            .iter()
+            .position(|c| *c == 0)
+            .ok_or(StatusError::BadBufferSize)?;
+        // Safety: The label is null-terminated, as checked above
+        Ok(unsafe { CStr16::from_u16_unsafe(&buf[..=end]) })
+    }
+
+    /// Closes the file handle, flushing any pending write.
+    ///
+    /// # Safety
+    /// The file can't be used once closed.
+    pub unsafe fn close(&self) -> EfiResult<()> {
+        unsafe { (self.0.close)(self as *const _ as *mut _) }.to_result()
+    }
+
+    /// Deletes the file, which also closes the handle. Returns `Warning::DeleteFailure` if the
+    /// handle was closed but the file couldn't be deleted.
+    ///
+    /// # Safety
+    /// The file can't be used once deleted, even if deleting it failed.
+    pub unsafe fn delete(&self) -> EfiResult<Option<Warning>> {
+        unsafe { (self.0.delete)(self as *const _ as *mut _) }.to_result_with_warning()
+    }
 }
 
 #[repr(C)]
@@ -166,10 +281,21 @@ struct RawFileProtocol {
         buffer_size: *mut usize,
         buffer: *mut c_void,
     ) -> Status,
-    write: *const c_void,
-    get_position: *const c_void,
+    write: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        buffer_size: *mut usize,
+        buffer: *const c_void,
+    ) -> Status,
+    get_position: unsafe extern "efiapi" fn(this: *mut Self, position: *mut u64) -> Status,
     set_position: unsafe extern "efiapi" fn(this: *mut Self, position: u64) -> Status,
-    flush: *const c_void,
+    get_info: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        information_type: *const Guid,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+    ) -> Status,
+    set_info: *const c_void,
+    flush: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
     open_ex: *const c_void,
     read_ex: *const c_void,
     write_ex: *const c_void,
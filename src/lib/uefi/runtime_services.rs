@@ -0,0 +1,377 @@
+use core::{ffi::c_void, fmt::Display, ptr};
+
+use bitflags::bitflags;
+
+use crate::guid;
+
+use super::{
+    memory_map::{MemoryDescriptor, MemoryMap},
+    status::{EfiResult, Status, StatusError},
+    string::CStr16,
+    Guid, TableHeader,
+};
+
+/// Vendor GUID used by all the architecturally defined variables (`BootOrder`, `Boot####`, ...)
+pub const GLOBAL_VARIABLE: Guid = guid!("8BE4DF61-93CA-11D2-AA0D-00E098032B8C");
+
+bitflags! {
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, Clone, Copy, Default)]
+    pub struct VariableAttributes: u32 {
+        const NonVolatile                       = 0x00000001;
+        const BootServiceAccess                 = 0x00000002;
+        const RuntimeAccess                     = 0x00000004;
+        const HardwareErrorRecord               = 0x00000008;
+        const AuthenticatedWriteAccess          = 0x00000010;
+        const TimeBasedAuthenticatedWriteAccess = 0x00000020;
+        const AppendWrite                       = 0x00000040;
+        const EnhancedAuthenticatedAccess       = 0x00000080;
+        const _                                 = !0;
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum ResetType {
+    Cold,
+    Warm,
+    Shutdown,
+    PlatformSpecific,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Time {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    _pad1: u8,
+    pub nanosecond: u32,
+    /// Offset from UTC in minutes, or `Time::UNSPECIFIED_TIMEZONE` if the time is local time
+    pub time_zone: i16,
+    pub daylight: u8,
+    _pad2: u8,
+}
+
+impl Time {
+    pub const UNSPECIFIED_TIMEZONE: i16 = 0x07FF;
+}
+
+impl Display for Time {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+
+        if self.time_zone != Self::UNSPECIFIED_TIMEZONE {
+            // Localtime = UTC + TimeZone
+            let sign = if self.time_zone < 0 { '-' } else { '+' };
+            let offset = self.time_zone.unsigned_abs();
+            write!(f, " UTC{}{:02}:{:02}", sign, offset / 60, offset % 60)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimeCapabilities {
+    /// Resolution of the real-time clock, in counts per second
+    pub resolution: u32,
+    /// Accuracy of the real-time clock, in parts per million
+    pub accuracy: u32,
+    sets_to_zero: u8,
+}
+
+impl TimeCapabilities {
+    /// Whether setting the time clears the sub-resolution part (below 1 / `resolution` seconds)
+    pub fn sets_to_zero(&self) -> bool {
+        self.sets_to_zero != 0
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VariableStorageInfo {
+    pub maximum_storage_size: u64,
+    pub remaining_storage_size: u64,
+    pub maximum_variable_size: u64,
+}
+
+/// Result of `RuntimeServices::get_next_variable_name`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NextVariableName {
+    /// The name and vendor of the next variable were written
+    Found,
+    /// Every variable has been enumerated
+    Done,
+    /// The name of the next variable needs a buffer of this many `u16`s (null terminator
+    /// included)
+    BufferTooSmall(usize),
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct RuntimeServices(*mut RawRuntimeServices);
+
+impl RuntimeServices {
+    /// `RUNTSERV`
+    pub const SIGNATURE: u64 = 0x5652_4553_544E_5552;
+
+    pub(crate) fn from_ptr(ptr: *mut RawRuntimeServices) -> Self {
+        Self(ptr)
+    }
+
+    pub fn header(&self) -> &TableHeader {
+        // Safety: The runtime services table stays valid for as long as PUB runs
+        unsafe { &(*self.0).hdr }
+    }
+
+    pub fn get_time(&self) -> EfiResult<Time> {
+        let mut time = Time::default();
+        // Safety: Capabilities are optional, the time struct is a valid pointer
+        unsafe { ((*self.0).get_time)(&mut time, ptr::null_mut()) }.to_result()?;
+        Ok(time)
+    }
+
+    pub fn get_time_capabilities(&self) -> EfiResult<(Time, TimeCapabilities)> {
+        let mut time = Time::default();
+        let mut capabilities = TimeCapabilities::default();
+        // Safety: Both structs are valid pointers
+        unsafe { ((*self.0).get_time)(&mut time, &mut capabilities) }.to_result()?;
+        Ok((time, capabilities))
+    }
+
+    pub fn set_time(&self, time: &Time) -> EfiResult<()> {
+        // Safety: The firmware validates the time fields and returns InvalidParameter if needed
+        unsafe { ((*self.0).set_time)(time) }.to_result()
+    }
+
+    /// Returns the size (in bytes) of the variable's data, without reading it.
+    pub fn get_variable_size(&self, name: &CStr16, vendor: &Guid) -> EfiResult<usize> {
+        let mut size = 0;
+        let res = unsafe {
+            ((*self.0).get_variable)(
+                name.as_ptr(),
+                vendor,
+                ptr::null_mut(),
+                &mut size,
+                ptr::null_mut(),
+            )
+        }
+        .to_result();
+
+        match res {
+            // Zero-sized variable
+            Ok(()) => Ok(0),
+            Err(StatusError::BufferTooSmall) => Ok(size),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads a variable into `buf`. Returns the number of bytes written along with the variable's
+    /// attributes. Returns `Err(BufferTooSmall)` if `buf` can't hold the whole variable, see
+    /// `get_variable_size`.
+    pub fn get_variable(
+        &self,
+        name: &CStr16,
+        vendor: &Guid,
+        buf: &mut [u8],
+    ) -> EfiResult<(usize, VariableAttributes)> {
+        let mut attributes = 0;
+        let mut size = buf.len();
+        // Safety: `size` matches the length of the buffer
+        unsafe {
+            ((*self.0).get_variable)(
+                name.as_ptr(),
+                vendor,
+                &mut attributes,
+                &mut size,
+                buf.as_mut_ptr() as *mut c_void,
+            )
+        }
+        .to_result()?;
+
+        Ok((size, VariableAttributes::from_bits_retain(attributes)))
+    }
+
+    /// Creates, updates or deletes (if `data` is empty) a variable.
+    pub fn set_variable(
+        &self,
+        name: &CStr16,
+        vendor: &Guid,
+        attributes: VariableAttributes,
+        data: &[u8],
+    ) -> EfiResult<()> {
+        // Safety: The data pointer is never written to by the firmware
+        unsafe {
+            ((*self.0).set_variable)(
+                name.as_ptr(),
+                vendor,
+                attributes.bits(),
+                data.len(),
+                data.as_ptr() as *const c_void,
+            )
+        }
+        .to_result()
+    }
+
+    pub fn delete_variable(&self, name: &CStr16, vendor: &Guid) -> EfiResult<()> {
+        self.set_variable(name, vendor, VariableAttributes::empty(), &[])
+    }
+
+    /// Gets the variable following `name`/`vendor`, and writes its name and vendor in place.
+    /// To start the enumeration, `name` should contain an empty (null-terminated) string.
+    ///
+    /// If the name of the next variable does not fit in `name`, the length it needs is returned
+    /// and `name` is left untouched, so the call can be retried with a larger buffer.
+    pub fn get_next_variable_name(
+        &self,
+        name: &mut [u16],
+        vendor: &mut Guid,
+    ) -> EfiResult<NextVariableName> {
+        if !name.contains(&0) {
+            return Err(StatusError::InvalidParameter);
+        }
+
+        let mut size = size_of_val(name);
+        // Safety: The name buffer is null-terminated and its size is given in bytes
+        let res =
+            unsafe { ((*self.0).get_next_variable_name)(&mut size, name.as_mut_ptr(), vendor) }
+                .to_result();
+
+        match res {
+            Ok(()) => Ok(NextVariableName::Found),
+            Err(StatusError::NotFound) => Ok(NextVariableName::Done),
+            Err(StatusError::BufferTooSmall) => {
+                Ok(NextVariableName::BufferTooSmall(size.div_ceil(2)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn query_variable_info(
+        &self,
+        attributes: VariableAttributes,
+    ) -> EfiResult<VariableStorageInfo> {
+        let mut info = VariableStorageInfo {
+            maximum_storage_size: 0,
+            remaining_storage_size: 0,
+            maximum_variable_size: 0,
+        };
+
+        unsafe {
+            ((*self.0).query_variable_info)(
+                attributes.bits(),
+                &mut info.maximum_storage_size,
+                &mut info.remaining_storage_size,
+                &mut info.maximum_variable_size,
+            )
+        }
+        .to_result()?;
+
+        Ok(info)
+    }
+
+    /// Switches the runtime services to the virtual addresses set in the `virtual_start` field of
+    /// every runtime descriptor of `map`.
+    ///
+    /// # Safety
+    /// Can only be called once, after exiting boot services. Once it succeeds, the runtime
+    /// services (and the system table) can only be used through their virtual addresses, this
+    /// `RuntimeServices` copy (which uses physical addresses) must not be used anymore.
+    pub unsafe fn set_virtual_address_map(&self, map: &mut MemoryMap) -> EfiResult<()> {
+        unsafe {
+            ((*self.0).set_virtual_address_map)(
+                map.size(),
+                map.descriptor_size(),
+                map.descriptor_version(),
+                map.as_mut_ptr(),
+            )
+        }
+        .to_result()
+    }
+
+    pub fn as_ptr(&self) -> *const c_void {
+        self.0 as *const c_void
+    }
+
+    /// Resets the whole platform. `data` may contain a null-terminated string optionally followed
+    /// by binary data, describing the reason for the reset.
+    pub fn reset_system(&self, reset_type: ResetType, status: Status, data: Option<&[u8]>) -> ! {
+        let (size, data) = match data {
+            Some(d) => (d.len(), d.as_ptr() as *const c_void),
+            None => (0, ptr::null()),
+        };
+
+        // Safety: ResetSystem never returns
+        unsafe { ((*self.0).reset_system)(reset_type, status, size, data) }
+    }
+}
+
+#[repr(C)]
+pub(crate) struct RawRuntimeServices {
+    hdr: TableHeader,
+    // Time Services
+    get_time:
+        unsafe extern "efiapi" fn(time: *mut Time, capabilities: *mut TimeCapabilities) -> Status,
+    set_time: unsafe extern "efiapi" fn(time: *const Time) -> Status,
+    get_wakeup_time: *const c_void,
+    set_wakeup_time: *const c_void,
+
+    // Virtual Memory Services
+    set_virtual_address_map: unsafe extern "efiapi" fn(
+        memory_map_size: usize,
+        descriptor_size: usize,
+        descriptor_version: u32,
+        virtual_map: *mut MemoryDescriptor,
+    ) -> Status,
+    convert_pointer: *const c_void,
+
+    // Variable Services
+    get_variable: unsafe extern "efiapi" fn(
+        variable_name: *const u16,
+        vendor_guid: *const Guid,
+        attributes: *mut u32,
+        data_size: *mut usize,
+        data: *mut c_void,
+    ) -> Status,
+    get_next_variable_name: unsafe extern "efiapi" fn(
+        variable_name_size: *mut usize,
+        variable_name: *mut u16,
+        vendor_guid: *mut Guid,
+    ) -> Status,
+    set_variable: unsafe extern "efiapi" fn(
+        variable_name: *const u16,
+        vendor_guid: *const Guid,
+        attributes: u32,
+        data_size: usize,
+        data: *const c_void,
+    ) -> Status,
+
+    // Miscellaneous Services
+    get_next_high_monotonic_count: *const c_void,
+    reset_system: unsafe extern "efiapi" fn(
+        reset_type: ResetType,
+        reset_status: Status,
+        data_size: usize,
+        reset_data: *const c_void,
+    ) -> !,
+
+    // Capsule Services
+    update_capsule: *const c_void,
+    query_capsule_capabilities: *const c_void,
+
+    // Miscellaneous Services
+    query_variable_info: unsafe extern "efiapi" fn(
+        attributes: u32,
+        maximum_variable_storage_size: *mut u64,
+        remaining_variable_storage_size: *mut u64,
+        maximum_variable_size: *mut u64,
+    ) -> Status,
+}
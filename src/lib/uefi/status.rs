@@ -1,19 +1,49 @@
+use core::fmt::Display;
+
 pub type EfiResult<T> = Result<T, StatusError>;
 
 #[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Status(usize);
 
 const UPPER_BIT_MASK: usize = 1 << (usize::BITS - 1);
+/// Set (along with the upper bit for errors) on codes reserved for OEMs
+const OEM_BIT_MASK: usize = 1 << (usize::BITS - 2);
+
 impl Status {
+    pub const SUCCESS: Self = Self(0);
+
     pub fn ok() -> Self {
-        Self(0)
+        Self::SUCCESS
+    }
+
+    pub fn raw(&self) -> usize {
+        self.0
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.0 & UPPER_BIT_MASK != 0
     }
 
+    /// Success codes other than 0 are warnings
+    pub fn is_warning(&self) -> bool {
+        self.0 != 0 && !self.is_error()
+    }
+
+    /// Converts the status to a result. Warnings are treated as a success, use
+    /// `to_result_with_warning` where they matter.
     pub fn to_result(self) -> Result<(), StatusError> {
-        if self.0 & UPPER_BIT_MASK == 0 {
-            Ok(())
-        } else {
+        self.to_result_with_warning().map(|_| ())
+    }
+
+    /// Converts the status to a result, keeping the warning (if any) on success.
+    pub fn to_result_with_warning(self) -> Result<Option<Warning>, StatusError> {
+        if self.is_error() {
             Err(self.0.into())
+        } else if self.is_warning() {
+            Ok(Some(self.0.into()))
+        } else {
+            Ok(None)
         }
     }
 }
@@ -24,9 +54,54 @@ impl From<usize> for Status {
     }
 }
 
-#[derive(Debug)]
+impl From<StatusError> for Status {
+    fn from(value: StatusError) -> Self {
+        Self(value.raw())
+    }
+}
+
+impl From<Warning> for Status {
+    fn from(value: Warning) -> Self {
+        Self(value.raw())
+    }
+}
+
+impl<T> From<EfiResult<T>> for Status {
+    fn from(value: EfiResult<T>) -> Self {
+        match value {
+            Ok(_) => Self::SUCCESS,
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl core::fmt::Debug for Status {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_error() {
+            write!(f, "{:?}", StatusError::from(self.0))
+        } else if self.is_warning() {
+            write!(f, "{:?}", Warning::from(self.0))
+        } else {
+            write!(f, "Success")
+        }
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_error() {
+            write!(f, "{}", StatusError::from(self.0))
+        } else if self.is_warning() {
+            write!(f, "{}", Warning::from(self.0))
+        } else {
+            write!(f, "The operation completed successfully.")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StatusError {
-    LoadError = 1,
+    LoadError,
     InvalidParameter,
     Unsupported,
     BadBufferSize,
@@ -60,15 +135,63 @@ pub enum StatusError {
     IpAddressConflict,
     HttpError,
 
-    // Currently, warnings also get mapped to `Unknown`, might want to handle those status codes
-    Unknown,
+    /// Error code reserved for OEMs, with its raw value (upper bits included)
+    Oem(usize),
+    /// Error code not defined by the spec (yet), with its raw value (upper bit included)
+    Unknown(usize),
+}
+
+impl StatusError {
+    /// Raw status value, upper bit included
+    pub fn raw(&self) -> usize {
+        let code = match self {
+            Self::LoadError => 1,
+            Self::InvalidParameter => 2,
+            Self::Unsupported => 3,
+            Self::BadBufferSize => 4,
+            Self::BufferTooSmall => 5,
+            Self::NotReady => 6,
+            Self::DeviceError => 7,
+            Self::WriteProtected => 8,
+            Self::OutOfResources => 9,
+            Self::VolumeCorrupted => 10,
+            Self::VolumeFull => 11,
+            Self::NoMedia => 12,
+            Self::MediaChanged => 13,
+            Self::NotFound => 14,
+            Self::AccessDenied => 15,
+            Self::NoResponse => 16,
+            Self::NoMapping => 17,
+            Self::Timeout => 18,
+            Self::NotStarted => 19,
+            Self::AlreadyStarted => 20,
+            Self::Aborted => 21,
+            Self::IcmpError => 22,
+            Self::TftpError => 23,
+            Self::ProtocolError => 24,
+            Self::IncompatibleVersion => 25,
+            Self::SecurityViolation => 26,
+            Self::CrcError => 27,
+            Self::EndOfMedia => 28,
+            Self::EndOfFile => 31,
+            Self::InvalidLanguage => 32,
+            Self::CompromisedData => 33,
+            Self::IpAddressConflict => 34,
+            Self::HttpError => 35,
+            Self::Oem(raw) | Self::Unknown(raw) => return *raw,
+        };
+        code | UPPER_BIT_MASK
+    }
 }
 
 impl From<usize> for StatusError {
     fn from(value: usize) -> Self {
+        if value & OEM_BIT_MASK != 0 {
+            return Self::Oem(value | UPPER_BIT_MASK);
+        }
+
         // Unset upper bit
-        let value = value & !UPPER_BIT_MASK;
-        match value {
+        match value & !UPPER_BIT_MASK {
             1 => Self::LoadError,
             2 => Self::InvalidParameter,
             3 => Self::Unsupported,
@@ -97,13 +220,147 @@ impl From<usize> for StatusError {
             26 => Self::SecurityViolation,
             27 => Self::CrcError,
             28 => Self::EndOfMedia,
-            29 => Self::EndOfFile,
-            30 => Self::InvalidLanguage,
-            31 => Self::CompromisedData,
-            32 => Self::IpAddressConflict,
-            33 => Self::HttpError,
+            31 => Self::EndOfFile,
+            32 => Self::InvalidLanguage,
+            33 => Self::CompromisedData,
+            34 => Self::IpAddressConflict,
+            35 => Self::HttpError,
+
+            _ => Self::Unknown(value | UPPER_BIT_MASK),
+        }
+    }
+}
+
+impl Display for StatusError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let description = match self {
+            Self::LoadError => "The image failed to load.",
+            Self::InvalidParameter => "A parameter was incorrect.",
+            Self::Unsupported => "The operation is not supported.",
+            Self::BadBufferSize => "The buffer was not the proper size for the request.",
+            Self::BufferTooSmall => {
+                "The buffer is not large enough to hold the requested data. The required buffer \
+                 size is returned in the appropriate parameter when this error occurs."
+            }
+            Self::NotReady => "There is no data pending upon return.",
+            Self::DeviceError => {
+                "The physical device reported an error while attempting the operation."
+            }
+            Self::WriteProtected => "The device cannot be written to.",
+            Self::OutOfResources => "A resource has run out.",
+            Self::VolumeCorrupted => {
+                "An inconstancy was detected on the file system causing the operation to fail."
+            }
+            Self::VolumeFull => "There is no more space on the file system.",
+            Self::NoMedia => "The device does not contain any medium to perform the operation.",
+            Self::MediaChanged => "The medium in the device has changed since the last access.",
+            Self::NotFound => "The item was not found.",
+            Self::AccessDenied => "Access was denied.",
+            Self::NoResponse => "The server was not found or did not respond to the request.",
+            Self::NoMapping => "A mapping to a device does not exist.",
+            Self::Timeout => "The timeout time expired.",
+            Self::NotStarted => "The protocol has not been started.",
+            Self::AlreadyStarted => "The protocol has already been started.",
+            Self::Aborted => "The operation was aborted.",
+            Self::IcmpError => "An ICMP error occurred during the network operation.",
+            Self::TftpError => "A TFTP error occurred during the network operation.",
+            Self::ProtocolError => "A protocol error occurred during the network operation.",
+            Self::IncompatibleVersion => {
+                "The function encountered an internal version that was incompatible with a \
+                 version requested by the caller."
+            }
+            Self::SecurityViolation => {
+                "The function was not performed due to a security violation."
+            }
+            Self::CrcError => "A CRC error was detected.",
+            Self::EndOfMedia => "Beginning or end of media was reached.",
+            Self::EndOfFile => "The end of the file was reached.",
+            Self::InvalidLanguage => "The language specified was invalid.",
+            Self::CompromisedData => {
+                "The security status of the data is unknown or compromised and the data must be \
+                 updated or replaced to restore a valid security status."
+            }
+            Self::IpAddressConflict => "There is an address conflict address allocation.",
+            Self::HttpError => "A HTTP error occurred during the network operation.",
+            Self::Oem(raw) => return write!(f, "OEM error {:#x}.", raw),
+            Self::Unknown(raw) => return write!(f, "Unknown error {:#x}.", raw),
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// `EFI_WARN_*` codes: the operation succeeded, but something may need attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    UnknownGlyph,
+    DeleteFailure,
+    WriteFailure,
+    BufferTooSmall,
+    StaleData,
+    FileSystem,
+    ResetRequired,
 
-            _ => Self::Unknown,
+    /// Warning code reserved for OEMs, with its raw value
+    Oem(usize),
+    /// Warning code not defined by the spec (yet), with its raw value
+    Unknown(usize),
+}
+
+impl Warning {
+    pub fn raw(&self) -> usize {
+        match self {
+            Self::UnknownGlyph => 1,
+            Self::DeleteFailure => 2,
+            Self::WriteFailure => 3,
+            Self::BufferTooSmall => 4,
+            Self::StaleData => 5,
+            Self::FileSystem => 6,
+            Self::ResetRequired => 7,
+            Self::Oem(raw) | Self::Unknown(raw) => *raw,
         }
     }
 }
+
+impl From<usize> for Warning {
+    fn from(value: usize) -> Self {
+        match value {
+            1 => Self::UnknownGlyph,
+            2 => Self::DeleteFailure,
+            3 => Self::WriteFailure,
+            4 => Self::BufferTooSmall,
+            5 => Self::StaleData,
+            6 => Self::FileSystem,
+            7 => Self::ResetRequired,
+            _ if value & OEM_BIT_MASK != 0 => Self::Oem(value),
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let description = match self {
+            Self::UnknownGlyph => {
+                "The string contained one or more characters that the device \
+                                  could not render and were skipped."
+            }
+            Self::DeleteFailure => "The handle was closed, but the file was not deleted.",
+            Self::WriteFailure => {
+                "The handle was closed, but the data to the file was not flushed properly."
+            }
+            Self::BufferTooSmall => {
+                "The resulting buffer was too small, and the data was truncated to the buffer \
+                 size."
+            }
+            Self::StaleData => {
+                "The data has not been updated within the timeframe set by local policy for \
+                 this type of data."
+            }
+            Self::FileSystem => "The resulting buffer contains UEFI-compliant file system.",
+            Self::ResetRequired => "The operation will be processed across a system reset.",
+            Self::Oem(raw) => return write!(f, "OEM warning {:#x}.", raw),
+            Self::Unknown(raw) => return write!(f, "Unknown warning {:#x}.", raw),
+        };
+        write!(f, "{}", description)
+    }
+}
@@ -1,4 +1,6 @@
-use core::slice;
+use core::{fmt::Display, ops::Deref, slice};
+
+use super::helper::AllocatedPool;
 
 #[repr(transparent)]
 pub struct CStr16([u16]);
@@ -27,7 +29,79 @@ impl CStr16 {
         Self::from_u16_unsafe(slice::from_raw_parts(ptr, length))
     }
 
+    /// Encodes `s` as UCS-2 into `buf`, and returns it as a &CStr16. Returns `None` if `s`
+    /// contains characters outside of the BMP or a null character, or if `buf` is too small to
+    /// hold the string and its null terminator.
+    pub fn from_str_with_buf<'a>(s: &str, buf: &'a mut [u16]) -> Option<&'a Self> {
+        let mut len = 0;
+        for c in s.chars() {
+            if c == '\0' || len + 1 >= buf.len() {
+                return None;
+            }
+
+            let mut utf16_buf = [0_u16; 2];
+            let encoded = c.encode_utf16(&mut utf16_buf);
+            if encoded.len() != 1 {
+                return None;
+            }
+
+            buf[len] = encoded[0];
+            len += 1;
+        }
+
+        if len >= buf.len() {
+            return None;
+        }
+        buf[len] = 0;
+
+        // Safety: Only UCS-2 characters were written, followed by a null terminator
+        unsafe { Some(Self::from_u16_unsafe(&buf[..=len])) }
+    }
+
     pub const fn as_ptr(&self) -> *const u16 {
         self.0.as_ptr()
     }
+
+    /// Number of characters, null terminator excluded
+    pub fn len(&self) -> usize {
+        self.0.iter().take_while(|c| **c != 0).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Decodes the string, up to its null terminator
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.0
+            .iter()
+            .take_while(|c| **c != 0)
+            .map(|c| char::from_u32(*c as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+}
+
+impl Display for CStr16 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.chars().try_for_each(|c| write!(f, "{}", c))
+    }
+}
+
+/// A string allocated by the firmware, freed once dropped
+pub struct PoolCStr16(AllocatedPool<[u16]>);
+
+impl PoolCStr16 {
+    /// # Safety
+    /// `pool` must hold a valid, null-terminated UCS-2 string.
+    pub(crate) unsafe fn new(pool: AllocatedPool<[u16]>) -> Self {
+        Self(pool)
+    }
+}
+
+impl Deref for PoolCStr16 {
+    type Target = CStr16;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The pool holds a null-terminated UCS-2 string
+        unsafe { CStr16::from_u16_unsafe(self.0.as_ref()) }
+    }
 }
@@ -0,0 +1,112 @@
+use core::{
+    cell::UnsafeCell,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use super::{boot_services::BootServices, event::Tpl};
+
+/// Raises the task priority level while it exists, so that event notifications at or below that
+/// level are held. PUB only runs on the boot processor, so this makes a critical section.
+pub struct TplGuard {
+    old: Tpl,
+    boot_services: BootServices,
+}
+
+impl TplGuard {
+    /// Raises the TPL to `tpl`, or keeps it as is if it's already higher.
+    pub fn new(boot_services: BootServices, tpl: Tpl) -> Self {
+        // Raising to HIGH_LEVEL is always allowed, and tells us the current TPL. Restoring to a
+        // level between the two is allowed too.
+        // Safety: The TPL is only lowered back to `old`, when the guard is dropped
+        let old = unsafe { boot_services.raise_tpl(Tpl::HIGH_LEVEL) };
+        unsafe { boot_services.restore_tpl(tpl.max(old)) };
+
+        Self { old, boot_services }
+    }
+
+    /// TPL which was active before the guard was created
+    pub fn old_tpl(&self) -> Tpl {
+        self.old
+    }
+}
+
+impl Drop for TplGuard {
+    fn drop(&mut self) {
+        // Safety: The TPL was raised from `old` by this guard
+        unsafe { self.boot_services.restore_tpl(self.old) };
+    }
+}
+
+/// A mutex for statics shared with event notifications. Locking raises the TPL to the highest
+/// level the data is used at, so no notification can touch it meanwhile.
+pub struct TplMutex<T> {
+    tpl: Tpl,
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+// Safety: Access to the data is serialized by raising the TPL, and PUB only runs on the boot
+// processor
+unsafe impl<T: Send> Sync for TplMutex<T> {}
+
+impl<T> TplMutex<T> {
+    /// `tpl` is the highest level the data is accessed at, usually the notify TPL of the events
+    /// using it.
+    pub const fn new(tpl: Tpl, data: T) -> Self {
+        Self {
+            tpl,
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Locks the mutex. Returns `None` if it is already locked, which means the caller is
+    /// re-entering it (e.g. a panic while printing), or is a notification above the mutex's TPL
+    /// which interrupted the owner.
+    pub fn try_lock(&self, boot_services: BootServices) -> Option<TplMutexGuard<'_, T>> {
+        let tpl = TplGuard::new(boot_services, self.tpl);
+        if self.locked.swap(true, Ordering::Acquire) {
+            return None;
+        }
+
+        Some(TplMutexGuard {
+            mutex: self,
+            _tpl: tpl,
+        })
+    }
+
+    /// Locks the mutex, panics if it's already locked (see `try_lock`).
+    pub fn lock(&self, boot_services: BootServices) -> TplMutexGuard<'_, T> {
+        self.try_lock(boot_services)
+            .expect("TplMutex locked twice, or above its TPL")
+    }
+}
+
+pub struct TplMutexGuard<'a, T> {
+    mutex: &'a TplMutex<T>,
+    // Dropped after `drop` unlocks the mutex, restoring the TPL last
+    _tpl: TplGuard,
+}
+
+impl<T> Deref for TplMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The mutex is locked, so nothing else accesses the data
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T> DerefMut for TplMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: The mutex is locked, so nothing else accesses the data
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T> Drop for TplMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.locked.store(false, Ordering::Release);
+    }
+}
@@ -2,6 +2,7 @@ use core::{ffi::c_void, fmt::Display};
 
 use lib::{
     elf::{Elf64Ehdr, Elf64Phdr, ElfClass, ElfDataLayout, ElfMachine, ElfSegmentType, ElfType},
+    handoff::BootInfo,
     uefi::{
         boot_services::BootServices, helper::AllocatedPool, protocols::FileProtocol,
         status::StatusError,
@@ -48,10 +49,14 @@ impl From<StatusError> for KernelHeaderValidationError {
     }
 }
 
+/// A kernel loaded in memory. The pages of its segments are freed once dropped, which should only
+/// happen if the kernel wasn't started, or returned.
 pub struct KernelFile {
     elf_header: Elf64Ehdr,
-    // For now, store the phdrs to keep them from getting freed (might not be needed)
-    _program_headers: AllocatedPool<[Elf64Phdr]>,
+    program_headers: AllocatedPool<[Elf64Phdr]>,
+    /// Number of `PT_LOAD` segments whose pages were allocated
+    loaded_segments: usize,
+    boot_services: BootServices,
 }
 
 impl KernelFile {
@@ -80,16 +85,23 @@ impl KernelFile {
             };
         }
 
+        // Pages allocated so far get freed if loading fails
+        let mut kernel = Self {
+            elf_header: ehdr,
+            program_headers: program_headers_pool,
+            loaded_segments: 0,
+            boot_services,
+        };
+
         // Load segments
-        for phdr in program_headers {
+        for phdr in kernel.program_headers.as_ref().iter() {
             if phdr.p_type() != ElfSegmentType::Load {
                 // Segment does not need to be loaded into memory
                 continue;
             }
-            // Pages are 4KiB each, round up
-            let page_count = phdr.p_memsz.div_ceil(0x1000) as usize;
-            let segment_base = phdr.p_vaddr - phdr.p_offset;
+            let (segment_base, page_count) = segment_pages(phdr);
             boot_services.leaky_allocate_pages_at_address(page_count, segment_base)?;
+            kernel.loaded_segments += 1;
             // Load segment into allocate page(s)
             file.set_position(phdr.p_offset)?;
             let ptr: *mut c_void = phdr.p_vaddr as *mut c_void;
@@ -97,10 +109,7 @@ impl KernelFile {
             unsafe { file.read_n_bytes(ptr, phdr.p_filesz as usize) }?;
         }
 
-        Ok(Self {
-            elf_header: ehdr,
-            _program_headers: program_headers_pool,
-        })
+        Ok(kernel)
     }
 
     fn validate_header(ehdr: &Elf64Ehdr) -> Result<(), KernelHeaderValidationError> {
@@ -128,9 +137,32 @@ impl KernelFile {
     }
 
     // # Safety
-    // The ELF entrypoint must not expect any arguments, and should return a usize
-    pub fn entrypoint(&self) -> unsafe extern "C" fn() -> usize {
+    // The ELF entrypoint must follow the System V ABI, take a pointer to the BootInfo as its only
+    // argument, and should return a usize
+    pub fn entrypoint(&self) -> unsafe extern "sysv64" fn(*const BootInfo) -> usize {
         let ptr = self.elf_header.e_entry as *const ();
         unsafe { core::mem::transmute(ptr) }
     }
 }
+
+impl Drop for KernelFile {
+    fn drop(&mut self) {
+        let loaded = self
+            .program_headers
+            .as_ref()
+            .iter()
+            .filter(|phdr| phdr.p_type() == ElfSegmentType::Load)
+            .take(self.loaded_segments);
+        for phdr in loaded {
+            let (segment_base, page_count) = segment_pages(phdr);
+            let _ = self.boot_services.free_pages(segment_base, page_count);
+        }
+    }
+}
+
+/// Address and number of the pages holding a `PT_LOAD` segment
+fn segment_pages(phdr: &Elf64Phdr) -> (u64, usize) {
+    // Pages are 4KiB each, round up
+    let page_count = phdr.p_memsz.div_ceil(0x1000) as usize;
+    (phdr.p_vaddr - phdr.p_offset, page_count)
+}
@@ -0,0 +1,36 @@
+use lib::{
+    smbios::{Smbios, SmbiosError, Uuid},
+    uefi::SystemTable,
+};
+
+/// Finds the SMBIOS structure table, preferring the 3.x entry point.
+pub fn find_smbios(system_table: &SystemTable) -> Option<Result<Smbios, SmbiosError>> {
+    // Safety: Entry points come from the configuration table, and memory is identity-mapped
+    unsafe {
+        match (
+            system_table.smbios3_entry_point(),
+            system_table.smbios_entry_point(),
+        ) {
+            (Some(entry_point), _) => Some(Smbios::from_entry_point3(entry_point)),
+            (None, Some(entry_point)) => Some(Smbios::from_entry_point(entry_point)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Identity of the machine PUB runs on, used to pick the boot entries meant for it
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Machine<'a> {
+    pub product_name: Option<&'a str>,
+    pub uuid: Option<Uuid>,
+}
+
+impl<'a> Machine<'a> {
+    pub fn from_smbios(smbios: &'a Smbios) -> Self {
+        let system = smbios.system();
+        Self {
+            product_name: system.and_then(|s| s.product_name()),
+            uuid: system.and_then(|s| s.uuid()),
+        }
+    }
+}
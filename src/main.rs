@@ -1,64 +1,551 @@
 #![no_std]
 #![no_main]
 
+mod acpi_override;
+mod args;
+mod boot;
+mod chainload;
+mod config;
+mod connect;
+mod entry;
+mod initrd;
 mod loader;
+mod machine;
+mod menu;
+mod shell;
+mod volume;
 
+use args::Args;
+use chainload::ChainloadError;
+use config::{Config, ConfigFile, CONFIG_PATH};
+use entry::EntryKind;
 use lib::{
-    cstr16, println,
+    cstr16, eprintln, println,
     uefi::{
+        boot_services::{BootServices, ProtocolRegistration},
         helper::{self},
         protocols::{
-            FileAttribute, FileMode, LoadedImageProtocol, Protocol, ProtocolLocateError,
-            SimpleFileSystemProtocol,
+            DevicePathProtocol, FileAttribute, FileMode, FileProtocol, LoadedImageProtocol,
+            Protocol, SimpleFileSystemProtocol,
         },
-        status::Status,
+        runtime_services::RuntimeServices,
+        status::{Status, StatusError},
+        string::CStr16,
         Handle, SystemTable,
     },
 };
-use loader::KernelFile;
-
-// Helper function for now
-fn unwrap_protocol_result<T>(res: Result<T, ProtocolLocateError>) -> T {
-    match res {
-        Ok(p) => return p,
-        Err(ProtocolLocateError::Unsupported) => println!("Unsupported protocol"),
-        Err(ProtocolLocateError::Error(_)) => println!("Other error"),
+use loader::{KernelFile, KernelHeaderValidationError};
+use machine::Machine;
+use menu::{CountdownResult, MenuAction};
+use volume::{Titles, TITLES_LEN};
+
+const MAX_PATH_LEN: usize = 256;
+/// Room for PUB's own load options, once decoded
+const MAX_ARGS_LEN: usize = 1024;
+/// Watchdog codes up to 0xFFFF are reserved for the firmware
+const WATCHDOG_CODE: u64 = 0x10000;
+
+/// Reports an error which prevents PUB from going further, and converts it to the status
+/// returned to the firmware.
+fn error_status(context: &str, error: StatusError) -> Status {
+    eprintln!("{}: {}", context, error);
+    error.into()
+}
+
+/// Converts a config path (which may use `/` as a separator) to a UEFI path
+fn path_to_cstr16<'a>(path: &str, buf: &'a mut [u16]) -> Option<&'a CStr16> {
+    CStr16::from_str_with_buf(path, buf)?;
+    let len = buf.iter().position(|c| *c == 0)?;
+    for c in buf[..len].iter_mut().filter(|c| **c == b'/' as u16) {
+        *c = b'\\' as u16;
+    }
+    // Safety: Only replaced characters of an already valid, null-terminated UCS-2 string
+    Some(unsafe { CStr16::from_u16_unsafe(&buf[..=len]) })
+}
+
+/// Checks the headers of the system, boot services and runtime services tables. Failures are
+/// reported, but PUB carries on: the tables are usually still usable.
+fn check_tables(system_table: &mut SystemTable) {
+    let boot_services = system_table.boot_services();
+    let runtime_services = system_table.runtime_services();
+    let tables = [
+        ("System", system_table.header(), SystemTable::SIGNATURE),
+        (
+            "Boot services",
+            boot_services.header(),
+            BootServices::SIGNATURE,
+        ),
+        (
+            "Runtime services",
+            runtime_services.header(),
+            RuntimeServices::SIGNATURE,
+        ),
+    ];
+
+    for (name, header, signature) in tables {
+        if let Err(e) = header.validate(signature, &boot_services) {
+            println!("{} table header is invalid: {}", name, e);
+        }
+    }
+}
+
+fn load_kernel(
+    path: &str,
+    root: &FileProtocol,
+    system_table: &mut SystemTable,
+) -> Result<KernelFile, StatusError> {
+    let mut path_buf = [0_u16; MAX_PATH_LEN];
+    let Some(kernel_path) = path_to_cstr16(path, &mut path_buf) else {
+        println!("Invalid kernel path: {}", path);
+        return Err(StatusError::InvalidParameter);
     };
-    panic!()
+    let kernel_file = root
+        .open(kernel_path, FileMode::Read, FileAttribute::default())
+        .inspect_err(|e| println!("Error opening kernel file: {}", e))?;
+    println!("Opened {}", path);
+
+    KernelFile::load_from_file(kernel_file, system_table.boot_services()).map_err(|e| {
+        println!("Error loading kernel file: {}", e);
+        match e {
+            KernelHeaderValidationError::EfiError(e) => e,
+            _ => StatusError::LoadError,
+        }
+    })
+}
+
+/// Why PUB gives control back to the firmware
+struct Exit {
+    status: Status,
+    /// Short description, passed to the firmware as exit data
+    data: Option<&'static CStr16>,
+}
+
+impl Exit {
+    fn new(status: impl Into<Status>, data: &'static CStr16) -> Self {
+        Self {
+            status: status.into(),
+            data: Some(data),
+        }
+    }
+}
+
+impl From<Status> for Exit {
+    fn from(status: Status) -> Self {
+        Self { status, data: None }
+    }
 }
 
 #[no_mangle]
 pub extern "efiapi" fn efi_main(image_handle: Handle, mut system_table: SystemTable) -> Status {
     helper::register_services(&system_table);
+    helper::register_image_handle(image_handle);
+
+    let exit = run(image_handle, &mut system_table);
+
+    // Everything PUB allocated was freed when `run` returned
+    let boot_services = system_table.boot_services();
+    let _ = boot_services.disable_watchdog_timer();
+    boot_services.exit(image_handle, exit.status, exit.data)
+}
+
+fn run(image_handle: Handle, system_table: &mut SystemTable) -> Exit {
     let boot_services = system_table.boot_services();
 
+    // Firmware often starts in 80x25, even on larger screens
+    let _ = system_table.stdout().set_largest_mode();
+
     println!("Hello, World!");
+    println!(
+        "UEFI {}, {} (firmware revision {:#x})",
+        system_table.uefi_revision(),
+        system_table.firmware_vendor(),
+        system_table.firmware_revision()
+    );
+    check_tables(system_table);
+    if let Ok(time) = system_table.runtime_services().get_time() {
+        println!("Current time: {}", time);
+    }
+
+    let loaded_image = match LoadedImageProtocol::open(image_handle, &boot_services) {
+        Ok(p) => p,
+        Err(e) => return error_status("Error opening the loaded image", e.into()).into(),
+    };
+
+    let mut args_buf = [0_u8; MAX_ARGS_LEN];
+    let args = match loaded_image.load_options().map(|o| o.args(&mut args_buf)) {
+        Some(Some(args)) => Args::parse(args).unwrap_or_else(|e| {
+            println!("Ignoring load options: {}", e);
+            Args::default()
+        }),
+        Some(None) => {
+            println!("Ignoring load options: too long");
+            Args::default()
+        }
+        None => Args::default(),
+    };
 
-    let res = LoadedImageProtocol::try_locate(image_handle, &boot_services);
-    let loaded_image = unwrap_protocol_result(res);
+    if let Ok(device_path) = DevicePathProtocol::open(loaded_image.device(), &boot_services) {
+        match loaded_image.file_path() {
+            Some(file_path) => println!("Booted from {}/{}", &*device_path, file_path),
+            None => println!("Booted from {}", &*device_path),
+        }
+    }
 
     // Get volume from our EFI app handle and open root path
-    let res = SimpleFileSystemProtocol::try_locate(loaded_image.device(), &boot_services);
-    let res = unwrap_protocol_result(res);
-    let root = res.open_volume().expect("error opening root volume");
+    let file_system = match SimpleFileSystemProtocol::open(loaded_image.device(), &boot_services) {
+        Ok(p) => p,
+        Err(e) => return error_status("Error opening the boot volume", e.into()).into(),
+    };
+    let root = match file_system.open_volume() {
+        Ok(root) => root,
+        Err(e) => return error_status("Error opening the root directory", e).into(),
+    };
 
-    // Open the kernel file
-    let kernel_file = root
-        .open(
-            cstr16!("kernel.bin"),
-            FileMode::Read,
-            FileAttribute::default(),
-        )
-        .expect("Error opening kernel.bin file");
-    println!("Opened the kernel.bin file");
+    let exit = boot(
+        image_handle,
+        loaded_image.device(),
+        root,
+        &args,
+        system_table,
+    );
+    // Safety: The root directory isn't used past this point
+    let _ = unsafe { root.close() };
+    exit
+}
 
-    let kernel = KernelFile::load_from_file(kernel_file, system_table.boot_services())
-        .expect("error reading kernel file");
+/// Adds the entries found on the volumes which showed up since the last call
+fn add_new_volumes<'a>(
+    config: &mut Config<'a>,
+    titles: &mut Titles<'a>,
+    registration: ProtocolRegistration,
+    boot_services: &BootServices,
+) {
+    while let Some(device) = boot_services.next_registered_handle(registration) {
+        // The protocol may be reinstalled on a volume we already know about
+        if config.entries().any(|e| e.device == Some(device)) {
+            continue;
+        }
+        volume::discover(device, boot_services, titles, |entry| {
+            if !config.add_entry(entry) {
+                println!("No room left for {}", entry.title);
+            }
+        });
+    }
+}
 
-    println!("Kernel file loaded");
+/// Shows the menu and boots the selected entry. Only returns if PUB should exit.
+fn boot(
+    image_handle: Handle,
+    boot_device: Handle,
+    root: &FileProtocol,
+    args: &Args,
+    system_table: &mut SystemTable,
+) -> Exit {
+    let boot_services = system_table.boot_services();
+    // Titles of the entries discovered on new volumes
+    let mut titles_buf = [0_u8; TITLES_LEN];
+    let mut titles = Titles::new(&mut titles_buf);
+
+    let mut path_buf = [0_u16; MAX_PATH_LEN];
+    let config_path = match args.config {
+        Some(path) => path_to_cstr16(path, &mut path_buf).unwrap_or_else(|| {
+            println!("Invalid config path: {}", path);
+            CONFIG_PATH
+        }),
+        None => CONFIG_PATH,
+    };
+    if args.verbose {
+        println!("Config file: {}", config_path);
+    }
+    let config_file = ConfigFile::read(root, config_path, boot_services).unwrap_or_else(|e| {
+        println!("{}", e);
+        None
+    });
+    if config_file.is_none() && args.config.is_some() {
+        println!("{} not found, using defaults", config_path);
+    }
+    let mut config = match config_file
+        .as_ref()
+        .map(|f| f.text().and_then(Config::parse))
+    {
+        Some(Ok(config)) => config,
+        Some(Err(e)) => {
+            println!("Invalid config file, using defaults: {}", e);
+            Config::fallback()
+        }
+        None => Config::fallback(),
+    };
+    if let Some(timeout) = args.timeout {
+        config.timeout = timeout;
+    }
+
+    // Entries can be restricted to some machines, so one ESP can serve several of them
+    let smbios = match machine::find_smbios(system_table) {
+        Some(Ok(smbios)) => Some(smbios),
+        Some(Err(e)) => {
+            println!("{}", e);
+            None
+        }
+        None => None,
+    };
+    let machine = smbios
+        .as_ref()
+        .map(Machine::from_smbios)
+        .unwrap_or_default();
+    if let Some(product_name) = machine.product_name {
+        println!("Machine: {}", product_name);
+    }
+    if !config.retain_machine(&machine) {
+        println!("No entry matches this machine, showing every entry");
+    }
+
+    // Volumes showing up while the menu is shown (e.g. USB sticks) get their entries added
+    let media_event = boot_services.create_plain_event().ok();
+    let registration = media_event.as_ref().and_then(|event| {
+        boot_services
+            .register_protocol_notify(&SimpleFileSystemProtocol::GUID, event)
+            .ok()
+    });
+
+    if config.connect != 0 {
+        match connect::connect_all(&boot_services, config.connect) {
+            Ok(connected) if connected.timed_out => println!(
+                "Connected {} controllers, timed out after {}s",
+                connected.handles, config.connect
+            ),
+            Ok(connected) => println!("Connected {} controllers", connected.handles),
+            Err(e) => println!("Error connecting controllers: {}", e),
+        }
+        // File systems which just got connected show up as new volumes
+        if let Some(registration) = registration {
+            add_new_volumes(&mut config, &mut titles, registration, &boot_services);
+        }
+        // Already handled, the menu doesn't need to be redrawn for them
+        if let Some(event) = &media_event {
+            let _ = event.check();
+        }
+    }
 
-    let exit_code = unsafe { kernel.entrypoint()() };
-    println!("Kernel exited with code: {}", exit_code);
+    let default = match args.entry {
+        Some(title) => config.entry_index(title).unwrap_or_else(|| {
+            println!("No entry named {}", title);
+            config.default_index()
+        }),
+        None => config.default_index(),
+    };
+    // Status of the last entry which returned to PUB, handed to the firmware if the user exits
+    let mut returned: Option<Status> = None;
+    let mut selected = default;
+    // Once a key was pressed, PUB waits for the user to pick an entry
+    let mut interactive = false;
+    // Boot messages and the output of returned entries are kept, until the user moves around
+    let mut clear = false;
+    // Overrides are installed in the firmware's tables and can't be undone, this is the entry
+    // which changed them. Installing them twice would duplicate them.
+    let mut acpi_applied: Option<usize> = None;
+
+    // Keystrokes typed before PUB started shouldn't cancel the countdown
+    let _ = system_table.stdin().reset(false);
+    let key_event = system_table.stdin().wait_for_key(boot_services);
+
+    let (entry, kernel_path) = loop {
+        // The firmware watchdog would reset the machine while sitting in the menu
+        let _ = boot_services.disable_watchdog_timer();
+        if clear {
+            let _ = system_table.stdout().clear_screen();
+            clear = false;
+        }
+        menu::show(system_table, &config, selected);
+
+        let media = registration.and(media_event.as_ref());
+        if interactive {
+            let input = system_table.stdin();
+            match menu::select(
+                &boot_services,
+                input,
+                config.entries().count(),
+                selected,
+                media,
+            ) {
+                Ok(MenuAction::Boot(i)) => selected = i,
+                Ok(MenuAction::Select(i)) => {
+                    selected = i;
+                    clear = true;
+                    continue;
+                }
+                Ok(MenuAction::CommandLine) => {
+                    if let Err(e) = shell::prompt(system_table) {
+                        println!("Error reading the keyboard: {}", e);
+                    }
+                    continue;
+                }
+                Ok(MenuAction::NewMedia) => {
+                    if let Some(registration) = registration {
+                        add_new_volumes(&mut config, &mut titles, registration, &boot_services);
+                    }
+                    continue;
+                }
+                Ok(MenuAction::Exit) => {
+                    return match returned {
+                        Some(status) => Exit::new(status, cstr16!("PUB: boot entry returned")),
+                        None => Exit::new(StatusError::Aborted, cstr16!("PUB: boot cancelled")),
+                    };
+                }
+                Err(e) => return Exit::new(e, cstr16!("PUB: error reading the keyboard")),
+            }
+        } else {
+            let title = config.entry(selected).expect("no boot entry").title;
+            let res = match returned {
+                // Booting the same entry again could loop forever
+                Some(_) => menu::countdown(
+                    &boot_services,
+                    &"Returning to the firmware",
+                    config.timeout,
+                    Some(&key_event),
+                    media,
+                ),
+                None => menu::countdown(
+                    &boot_services,
+                    &format_args!("Booting {}", title),
+                    config.timeout,
+                    Some(&key_event),
+                    media,
+                ),
+            };
+            match res {
+                Ok(CountdownResult::Cancelled) => {
+                    // The key only stops the countdown
+                    let _ = system_table.stdin().read_key_stroke();
+                    interactive = true;
+                    clear = true;
+                    continue;
+                }
+                Ok(CountdownResult::NewMedia) => {
+                    if let Some(registration) = registration {
+                        add_new_volumes(&mut config, &mut titles, registration, &boot_services);
+                    }
+                    continue;
+                }
+                _ => {
+                    if let Some(status) = returned {
+                        return Exit::new(status, cstr16!("PUB: boot entry returned"));
+                    }
+                }
+            }
+        }
+
+        let entry = *config.entry(selected).expect("no boot entry");
+        match acpi_applied {
+            Some(i) if i != selected && !entry.acpi.is_empty() => {
+                let other = config.entry(i).expect("no boot entry").title;
+                eprintln!(
+                    "Not booting {}: the ACPI tables were already changed by {}",
+                    entry.title, other
+                );
+                interactive = true;
+                continue;
+            }
+            Some(i) if i != selected => {
+                let other = config.entry(i).expect("no boot entry").title;
+                eprintln!("Warning: the ACPI tables were changed by {}", other);
+            }
+            _ => {}
+        }
+        println!("Booting {}", entry.title);
+
+        if !entry.acpi.is_empty() {
+            if acpi_applied.is_some() {
+                println!("ACPI overrides were already applied, not applying them again");
+            } else {
+                let mut changed = false;
+                let res = acpi_override::apply(&entry.acpi, root, system_table, &mut changed);
+                // Tables installed before an error are there to stay
+                if changed {
+                    acpi_applied = Some(selected);
+                }
+                if let Err(e) = res {
+                    println!("Error applying ACPI overrides: {}", e);
+                }
+            }
+        }
+
+        let status = match entry.kind {
+            EntryKind::Kernel(path) => break (entry, path),
+            EntryKind::Command(line) => {
+                shell::run(line, system_table);
+                Status::SUCCESS
+            }
+            EntryKind::Chainload(path) => {
+                let res =
+                    chainload::chainload(path, &entry, boot_device, image_handle, system_table);
+                match res {
+                    Ok(exit) => {
+                        match exit.data {
+                            Some(data) => {
+                                println!("{} exited: {} ({})", path, exit.status, &*data)
+                            }
+                            None => println!("{} exited: {}", path, exit.status),
+                        }
+                        exit.status
+                    }
+                    Err(e) => {
+                        println!("Error starting {}: {}", path, e);
+                        match e {
+                            ChainloadError::EfiError(e) => e.into(),
+                            _ => StatusError::InvalidParameter.into(),
+                        }
+                    }
+                }
+            }
+        };
+        println!("Back from {}", entry.title);
+        returned = Some(status);
+        // The menu counts down again, without a key press the status goes to the firmware
+        let _ = system_table.stdin().reset(false);
+        interactive = false;
+    };
+
+    // A hung load resets the machine
+    if config.watchdog != 0 {
+        let _ = boot_services.set_watchdog_timer(
+            config.watchdog,
+            WATCHDOG_CODE,
+            Some(cstr16!("PUB: timed out loading boot entry")),
+        );
+    }
+
+    let res = match entry.device {
+        Some(device) => volume::with_root(device, &boot_services, |root| {
+            load_kernel(kernel_path, root, system_table)
+        })
+        .and_then(|res| res),
+        None => load_kernel(kernel_path, root, system_table),
+    };
+    let kernel = match res {
+        Ok(kernel) => kernel,
+        // Back to the firmware, which moves on to the next boot option
+        Err(e) => return Exit::new(e, cstr16!("PUB: error loading the kernel")),
+    };
+
+    println!("Kernel file loaded");
 
-    loop {}
+    if let Some(handoff) = entry.runtime {
+        // Only returns if boot services couldn't be exited
+        let Err(e) = boot::start_kernel_with_runtime(
+            &kernel,
+            handoff,
+            entry.acpi_info,
+            image_handle,
+            system_table,
+        );
+        println!("Error exiting boot services: {:?}", e);
+        Exit::new(e, cstr16!("PUB: error exiting boot services"))
+    } else {
+        // The kernel takes over, the watchdog is only meant for the loading phase
+        let _ = boot_services.disable_watchdog_timer();
+        let exit_code = boot::start_kernel(&kernel, entry.acpi_info, system_table);
+        println!("Kernel exited with code: {}", exit_code);
+        // The kernel follows the UEFI convention, its exit code is a status
+        Exit::new(Status::from(exit_code), cstr16!("PUB: kernel returned"))
+    }
 }
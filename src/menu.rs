@@ -0,0 +1,163 @@
+use core::fmt::Display;
+
+use lib::{
+    print, println,
+    uefi::{
+        boot_services::BootServices,
+        event::{Event, TimerDelay},
+        protocols::{Color, Input, Key},
+        status::EfiResult,
+        SystemTable,
+    },
+};
+
+use crate::config::Config;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CountdownResult {
+    Expired,
+    Cancelled,
+    /// A volume showed up, the entries may have changed
+    NewMedia,
+}
+
+/// What the user picked in the menu
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuAction {
+    Boot(usize),
+    /// The selection moved, the menu needs to be shown again
+    Select(usize),
+    CommandLine,
+    Exit,
+    /// A volume showed up, the entries may have changed
+    NewMedia,
+}
+
+/// Lists the entries, the selected one being highlighted.
+pub fn show(system_table: &mut SystemTable, config: &Config, selected: usize) {
+    let (foreground, background) = system_table.stdout().mode().colors();
+
+    println!();
+    for (i, entry) in config.entries().enumerate() {
+        if i == selected {
+            // The marker still shows the selection on consoles without colors. Swapping the
+            // colors would fail with a bright foreground, backgrounds only go up to light gray.
+            let _ = system_table
+                .stdout()
+                .set_color(Color::Black, Color::LightGray);
+            print!(" > {}. {}", i + 1, entry.title);
+            let _ = system_table.stdout().set_color(foreground, background);
+            println!();
+        } else {
+            println!("   {}. {}", i + 1, entry.title);
+        }
+    }
+    println!();
+}
+
+/// Waits for a key and maps it to a menu action: arrows move the selection, Enter or a digit
+/// boot an entry, `c` opens the command line and Esc exits. Returns early if `media` gets
+/// signaled.
+pub fn select(
+    boot_services: &BootServices,
+    input: &mut Input,
+    entry_count: usize,
+    selected: usize,
+    media: Option<&Event>,
+) -> EfiResult<MenuAction> {
+    let key_event = input.wait_for_key(*boot_services);
+    let mut events = [&key_event; 2];
+    let mut count = 1;
+    if let Some(media) = media {
+        events[1] = media;
+        count += 1;
+    }
+
+    print!("\rEnter: boot, c: command line, Esc: exit  ");
+    loop {
+        if boot_services.wait_for_any(&events[..count])? == 1 {
+            println!();
+            return Ok(MenuAction::NewMedia);
+        }
+        let Some(key) = input.read_key_stroke()? else {
+            continue;
+        };
+
+        let action = match key {
+            Key::Up => MenuAction::Select((selected + entry_count - 1) % entry_count),
+            Key::Down => MenuAction::Select((selected + 1) % entry_count),
+            Key::Home => MenuAction::Select(0),
+            Key::End => MenuAction::Select(entry_count - 1),
+            Key::Enter => MenuAction::Boot(selected),
+            Key::Char(c @ '1'..='9') if (c as usize - '1' as usize) < entry_count => {
+                MenuAction::Boot(c as usize - '1' as usize)
+            }
+            Key::Char('c') => MenuAction::CommandLine,
+            Key::Escape => MenuAction::Exit,
+            _ => continue,
+        };
+        println!();
+        return Ok(action);
+    }
+}
+
+/// Counts down from `seconds` before `action` (e.g. `Booting PamOS`), refreshing the message every
+/// second. Returns early if `cancel` (usually the console's `WaitForKey`) or `media` gets signaled.
+pub fn countdown(
+    boot_services: &BootServices,
+    action: &dyn Display,
+    seconds: usize,
+    cancel: Option<&Event>,
+    media: Option<&Event>,
+) -> EfiResult<CountdownResult> {
+    let timer = boot_services.create_timer()?;
+    timer.set_timer(TimerDelay::Periodic(TimerDelay::SECOND))?;
+
+    // The timer comes first, followed by the optional events
+    let mut events = [&timer; 3];
+    let mut results = [CountdownResult::Expired; 3];
+    let mut count = 1;
+    let optional = [
+        (cancel, CountdownResult::Cancelled),
+        (media, CountdownResult::NewMedia),
+    ];
+    for (event, result) in optional {
+        if let Some(event) = event {
+            events[count] = event;
+            results[count] = result;
+            count += 1;
+        }
+    }
+
+    let res = run_countdown(
+        boot_services,
+        action,
+        seconds,
+        &events[..count],
+        &results[..count],
+    );
+    println!();
+    res
+}
+
+/// The first event must be a periodic 1 second timer, the other ones end the countdown with the
+/// matching result.
+fn run_countdown(
+    boot_services: &BootServices,
+    action: &dyn Display,
+    seconds: usize,
+    events: &[&Event],
+    results: &[CountdownResult],
+) -> EfiResult<CountdownResult> {
+    for remaining in (1..=seconds).rev() {
+        // Trailing spaces clear leftovers of longer messages
+        print!("\r{} in {} seconds...  ", action, remaining);
+
+        match boot_services.wait_for_any(events)? {
+            0 => {}
+            i => return Ok(results[i]),
+        }
+    }
+
+    Ok(CountdownResult::Expired)
+}
@@ -0,0 +1,123 @@
+mod acpi;
+mod lsdev;
+mod sysinfo;
+
+use lib::{
+    print, println,
+    uefi::{
+        boot_services::BootServices,
+        protocols::{Input, Key},
+        status::EfiResult,
+        SystemTable,
+    },
+};
+
+/// Longest command line typed at the prompt, in bytes
+const MAX_LINE_LEN: usize = 256;
+
+struct Command {
+    name: &'static str,
+    help: &'static str,
+    run: fn(&mut SystemTable, &str),
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        help: "list the available commands",
+        run: help,
+    },
+    Command {
+        name: "acpi",
+        help: "show the ACPI tables and the decoded MADT, FADT, HPET and MCFG",
+        run: acpi::run,
+    },
+    Command {
+        name: "lsdev",
+        help: "list the handles with their protocols, device path and volume label",
+        run: lsdev::run,
+    },
+    Command {
+        name: "sysinfo",
+        help: "show the SMBIOS system, board, processor and memory information",
+        run: sysinfo::run,
+    },
+];
+
+/// Runs a single command line, which is made of a command name followed by its arguments.
+pub fn run(line: &str, system_table: &mut SystemTable) {
+    let line = line.trim();
+    let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+    if name.is_empty() {
+        return;
+    }
+
+    match COMMANDS.iter().find(|c| c.name == name) {
+        Some(command) => (command.run)(system_table, args.trim()),
+        None => println!("Unknown command: {} (try `help`)", name),
+    }
+}
+
+/// Reads and runs command lines until Esc is pressed or `exit` is typed.
+pub fn prompt(system_table: &mut SystemTable) -> EfiResult<()> {
+    let boot_services = system_table.boot_services();
+    println!("Type `help` to list the commands, `exit` or Esc to go back to the menu");
+
+    let mut buf = [0_u8; MAX_LINE_LEN];
+    loop {
+        print!("pub> ");
+        let Some(line) = read_line(system_table.stdin(), boot_services, &mut buf)? else {
+            return Ok(());
+        };
+        match line.trim() {
+            "exit" => return Ok(()),
+            line => run(line, system_table),
+        }
+    }
+}
+
+/// Reads a line, echoing it. Returns `None` if Esc was pressed.
+fn read_line<'a>(
+    input: &mut Input,
+    boot_services: BootServices,
+    buf: &'a mut [u8],
+) -> EfiResult<Option<&'a str>> {
+    let mut len = 0;
+    loop {
+        match input.read_key(boot_services)? {
+            Key::Enter => break,
+            Key::Escape => {
+                println!();
+                return Ok(None);
+            }
+            Key::Backspace => {
+                // Remove the whole (possibly multi-byte) last char
+                let Some(last) = core::str::from_utf8(&buf[..len])
+                    .ok()
+                    .and_then(|s| s.chars().next_back())
+                else {
+                    continue;
+                };
+                len -= last.len_utf8();
+                // Moves back, blanks the char and moves back again
+                print!("\u{8} \u{8}");
+            }
+            Key::Char(c) if len + c.len_utf8() <= buf.len() => {
+                c.encode_utf8(&mut buf[len..]);
+                len += c.len_utf8();
+                print!("{}", c);
+            }
+            _ => {}
+        }
+    }
+    println!();
+
+    // Only whole chars were written
+    Ok(core::str::from_utf8(&buf[..len]).ok())
+}
+
+fn help(_system_table: &mut SystemTable, _args: &str) {
+    for command in COMMANDS {
+        println!("{:<10} {}", command.name, command.help);
+    }
+}
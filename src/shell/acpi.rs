@@ -0,0 +1,160 @@
+use lib::{
+    acpi::{signature_str, Acpi, AcpiError, MadtEntry, MADT_CPU_ENABLED},
+    println,
+    uefi::SystemTable,
+};
+
+pub fn run(system_table: &mut SystemTable, _args: &str) {
+    let Some(rsdp) = system_table.acpi_rsdp() else {
+        println!("No ACPI RSDP in the configuration table");
+        return;
+    };
+
+    // Safety: The RSDP comes from the configuration table, and memory is identity-mapped
+    let acpi = match unsafe { Acpi::from_rsdp(rsdp) } {
+        Ok(acpi) => acpi,
+        Err(e) => {
+            println!("Invalid ACPI tables: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = show(&acpi) {
+        println!("Error: {}", e);
+    }
+}
+
+fn show(acpi: &Acpi) -> Result<(), AcpiError> {
+    let rsdp = acpi.rsdp();
+    let root = acpi.root_table();
+    println!(
+        "RSDP rev {} ({}), {} at {:#x}",
+        acpi.revision(),
+        signature_str(&rsdp.oem_id),
+        signature_str(&root.signature()),
+        root.address()
+    );
+
+    println!("Tables:");
+    for table in acpi.tables() {
+        let header = table.header();
+        println!(
+            "  {} {:#012x} len {:>6} rev {} {} {}{}",
+            signature_str(&header.signature),
+            table.address(),
+            table.len(),
+            header.revision,
+            signature_str(&header.oem_id),
+            signature_str(&header.oem_table_id),
+            if table.checksum_valid() {
+                ""
+            } else {
+                " (bad checksum)"
+            }
+        );
+    }
+
+    if let Some(madt) = acpi.madt()? {
+        println!(
+            "MADT: local APIC at {:#x}, {} usable CPU(s){}",
+            madt.local_apic_address(),
+            madt.processor_count(),
+            if madt.pcat_compat() {
+                ", 8259 PICs"
+            } else {
+                ""
+            }
+        );
+        for entry in madt.entries() {
+            match entry {
+                MadtEntry::LocalApic {
+                    processor_uid,
+                    apic_id,
+                    flags,
+                } => println!(
+                    "  CPU {} APIC ID {}{}",
+                    processor_uid,
+                    apic_id,
+                    enabled_str(flags)
+                ),
+                MadtEntry::LocalX2Apic {
+                    x2apic_id,
+                    flags,
+                    processor_uid,
+                } => println!(
+                    "  CPU {} x2APIC ID {}{}",
+                    processor_uid,
+                    x2apic_id,
+                    enabled_str(flags)
+                ),
+                MadtEntry::IoApic {
+                    id,
+                    address,
+                    gsi_base,
+                } => println!("  I/O APIC {} at {:#x}, GSI base {}", id, address, gsi_base),
+                MadtEntry::InterruptSourceOverride {
+                    bus,
+                    source,
+                    gsi,
+                    flags,
+                } => println!(
+                    "  IRQ override bus {} IRQ {} -> GSI {} (flags {:#x})",
+                    bus, source, gsi, flags
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(fadt) = acpi.fadt()? {
+        println!(
+            "FADT: SCI {}, DSDT at {:#x}, flags {:#x}, boot arch {:#x}{}",
+            fadt.sci_interrupt(),
+            fadt.dsdt_address(),
+            fadt.flags(),
+            fadt.boot_arch_flags(),
+            if fadt.hardware_reduced() {
+                ", hardware-reduced"
+            } else {
+                ""
+            }
+        );
+        if let Some((register, value)) = fadt.reset_register() {
+            println!("  Reset register {} value {:#x}", register, value);
+        }
+        if let Some(pm1a) = fadt.pm1a_control_block() {
+            println!("  PM1a control block {}", pm1a);
+        }
+    }
+
+    if let Some(hpet) = acpi.hpet()? {
+        println!(
+            "HPET {}: vendor {:#06x}, base {}, {} comparators, minimum tick {}",
+            hpet.hpet_number(),
+            hpet.pci_vendor_id(),
+            hpet.base_address(),
+            hpet.comparator_count(),
+            hpet.minimum_tick()
+        );
+    }
+
+    if let Some(mcfg) = acpi.mcfg()? {
+        println!("MCFG:");
+        for entry in mcfg.entries() {
+            println!(
+                "  Segment {} buses {:#04x}-{:#04x} at {:#x}",
+                entry.segment_group, entry.start_bus, entry.end_bus, entry.base_address
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn enabled_str(flags: u32) -> &'static str {
+    if flags & MADT_CPU_ENABLED != 0 {
+        ""
+    } else {
+        " (disabled)"
+    }
+}
@@ -0,0 +1,101 @@
+use lib::{
+    print, println,
+    uefi::{
+        boot_services::{BootServices, SearchType},
+        protocols::{
+            DevicePathProtocol, DevicePathToTextProtocol, Protocol, SimpleFileSystemProtocol,
+        },
+        Guid, Handle, SystemTable,
+    },
+};
+
+use crate::volume;
+
+/// `lsdev` lists every handle, `lsdev <index>` a single one (index in hex, as printed) and
+/// `lsdev <protocol>` the handles supporting a protocol (by name or GUID).
+pub fn run(system_table: &mut SystemTable, args: &str) {
+    let boot_services = system_table.boot_services();
+    let handles = match boot_services.locate_handle_buffer(SearchType::AllHandles) {
+        Ok(handles) => handles,
+        Err(e) => {
+            println!("Error listing handles: {}", e);
+            return;
+        }
+    };
+
+    if let Ok(index) = usize::from_str_radix(args, 16) {
+        match handles.as_ref().get(index) {
+            Some(handle) => show(index, *handle, &boot_services),
+            None => println!(
+                "No handle {:X}, there are {:X}",
+                index,
+                handles.as_ref().len()
+            ),
+        }
+        return;
+    }
+
+    let protocol = match args {
+        "" => None,
+        args => match find_protocol(args) {
+            Some(guid) => Some(guid),
+            None => {
+                println!("Unknown protocol: {}", args);
+                return;
+            }
+        },
+    };
+
+    for (index, handle) in handles.as_ref().iter().enumerate() {
+        let supported = protocol.is_none_or(|protocol| {
+            boot_services
+                .protocols_per_handle(*handle)
+                .is_ok_and(|guids| guids.iter().any(|g| *g == protocol))
+        });
+        if supported {
+            show(index, *handle, &boot_services);
+        }
+    }
+}
+
+/// Looks a protocol up by its name (case insensitive) or its GUID
+fn find_protocol(name: &str) -> Option<Guid> {
+    Guid::parse(name).or_else(|| {
+        Guid::known()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name))
+            .map(|(guid, _)| guid)
+    })
+}
+
+fn show(index: usize, handle: Handle, boot_services: &BootServices) {
+    print!("{:3X}: {:p}", index, handle.as_ptr());
+    match boot_services.protocols_per_handle(handle) {
+        Ok(guids) => guids.iter().for_each(|guid| match guid.name() {
+            Some(name) => print!(" {}", name),
+            None => print!(" {}", guid),
+        }),
+        Err(e) => print!(" ({})", e),
+    }
+    println!();
+
+    if let Ok(path) = DevicePathProtocol::open(handle, boot_services) {
+        let text = boot_services
+            .locate_protocol::<DevicePathToTextProtocol>()
+            .ok()
+            .and_then(|p| p.convert_device_path_to_text(&path, false, *boot_services));
+        match text {
+            Some(text) => println!("     {}", &*text),
+            // Generic form, not as readable but always available
+            None => println!("     {}", &*path),
+        }
+    }
+
+    if let Ok(file_system) = SimpleFileSystemProtocol::open(handle, boot_services) {
+        let mut buf = [0_u16; volume::MAX_LABEL_LEN];
+        match file_system.volume_label(&mut buf) {
+            Ok(label) if !label.is_empty() => println!("     Volume \"{}\"", label),
+            Ok(_) => println!("     Volume (no label)"),
+            Err(e) => println!("     Volume (error reading label: {})", e),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use lib::{println, smbios::Smbios, uefi::SystemTable};
+
+use crate::machine::find_smbios;
+
+pub fn run(system_table: &mut SystemTable, _args: &str) {
+    println!(
+        "Firmware:  {} (revision {:#x}), UEFI {}",
+        system_table.firmware_vendor(),
+        system_table.firmware_revision(),
+        system_table.uefi_revision()
+    );
+
+    let smbios = match find_smbios(system_table) {
+        Some(Ok(smbios)) => smbios,
+        Some(Err(e)) => {
+            println!("{}", e);
+            return;
+        }
+        None => {
+            println!("No SMBIOS entry point in the configuration table");
+            return;
+        }
+    };
+
+    show(&smbios);
+}
+
+fn show(smbios: &Smbios) {
+    let (major, minor) = smbios.version();
+    println!(
+        "SMBIOS {}.{}, table at {:#x}",
+        major,
+        minor,
+        smbios.table_address()
+    );
+
+    if let Some(bios) = smbios.bios() {
+        println!(
+            "BIOS:      {} {} ({})",
+            bios.vendor().unwrap_or("?"),
+            bios.version().unwrap_or("?"),
+            bios.release_date().unwrap_or("?")
+        );
+    }
+
+    if let Some(system) = smbios.system() {
+        println!(
+            "System:    {} {} {}",
+            system.manufacturer().unwrap_or("?"),
+            system.product_name().unwrap_or("?"),
+            system.version().unwrap_or("")
+        );
+        println!("  Serial:  {}", system.serial_number().unwrap_or("?"));
+        match system.uuid() {
+            Some(uuid) => println!("  UUID:    {}", uuid),
+            None => println!("  UUID:    not available"),
+        }
+    }
+
+    if let Some(board) = smbios.baseboard() {
+        println!(
+            "Board:     {} {} {}",
+            board.manufacturer().unwrap_or("?"),
+            board.product().unwrap_or("?"),
+            board.version().unwrap_or("")
+        );
+    }
+
+    for cpu in smbios.processors().filter(|p| p.is_populated()) {
+        println!(
+            "CPU {}: {}",
+            cpu.socket().unwrap_or("?"),
+            cpu.version().unwrap_or("?")
+        );
+        println!(
+            "  {} MHz (max {} MHz), {} cores, {} threads",
+            cpu.current_speed().unwrap_or(0),
+            cpu.max_speed().unwrap_or(0),
+            cpu.core_count().unwrap_or(0),
+            cpu.thread_count().unwrap_or(0)
+        );
+    }
+
+    let mut total = 0;
+    for dimm in smbios.memory_devices().filter(|m| m.is_installed()) {
+        let size = dimm.size().unwrap_or(0);
+        total += size;
+        println!(
+            "{}: {} MiB {} {} MT/s, {} {}",
+            dimm.device_locator().unwrap_or("?"),
+            size >> 20,
+            dimm.memory_type_name(),
+            dimm.configured_speed().or(dimm.speed()).unwrap_or(0),
+            dimm.manufacturer().unwrap_or("?"),
+            dimm.part_number().unwrap_or("")
+        );
+    }
+    println!("Memory:    {} MiB installed", total >> 20);
+}
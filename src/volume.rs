@@ -0,0 +1,183 @@
+use core::{
+    fmt::{self, Write},
+    mem, str,
+};
+
+use lib::{
+    println,
+    uefi::{
+        boot_services::BootServices,
+        device_path::{ACPI_TYPE, HARDWARE_TYPE, MESSAGING_TYPE},
+        protocols::{
+            DevicePathProtocol, FileAttribute, FileMode, FileProtocol, Protocol,
+            SimpleFileSystemProtocol,
+        },
+        status::{EfiResult, StatusError},
+        Handle,
+    },
+};
+
+use crate::{
+    entry::{BootEntry, EntryKind},
+    path_to_cstr16, MAX_PATH_LEN,
+};
+
+pub const MAX_LABEL_LEN: usize = 64;
+/// Bytes of storage for the titles of discovered entries
+pub const TITLES_LEN: usize = 1024;
+
+/// Default loader path of removable media
+const REMOVABLE_LOADER_PATH: &str = "EFI/BOOT/BOOTX64.EFI";
+/// Same as the fallback entry
+const KERNEL_PATH: &str = "kernel.bin";
+
+/// Finds the volume with the given label (case insensitive)
+pub fn find_by_label(label: &str, boot_services: &BootServices) -> Option<Handle> {
+    let handles = SimpleFileSystemProtocol::find_handles(boot_services).ok()?;
+    let mut buf = [0_u16; MAX_LABEL_LEN];
+
+    handles.as_ref().iter().copied().find(|handle| {
+        let Ok(file_system) = SimpleFileSystemProtocol::open(*handle, boot_services) else {
+            return false;
+        };
+        file_system.volume_label(&mut buf).is_ok_and(|l| {
+            l.chars()
+                .map(|c| c.to_ascii_uppercase())
+                .eq(label.chars().map(|c| c.to_ascii_uppercase()))
+        })
+    })
+}
+
+/// Runs `f` with the root directory of the volume on `device`.
+pub fn with_root<R>(
+    device: Handle,
+    boot_services: &BootServices,
+    f: impl FnOnce(&FileProtocol) -> R,
+) -> EfiResult<R> {
+    let file_system =
+        SimpleFileSystemProtocol::open(device, boot_services).map_err(StatusError::from)?;
+    let root = file_system.open_volume()?;
+    let res = f(root);
+    // Safety: The root directory isn't used past this point
+    let _ = unsafe { root.close() };
+    Ok(res)
+}
+
+/// Looks for a removable media loader and a PamOS kernel on a volume which just showed up, and
+/// calls `add` with an entry for each of them. Entries are titled after the volume's label, or
+/// its device if it has none.
+pub fn discover<'a>(
+    device: Handle,
+    boot_services: &BootServices,
+    titles: &mut Titles<'a>,
+    mut add: impl FnMut(BootEntry<'a>),
+) {
+    let candidates = [
+        ("EFI loader", EntryKind::Chainload(REMOVABLE_LOADER_PATH)),
+        ("kernel", EntryKind::Kernel(KERNEL_PATH)),
+    ];
+
+    let res = with_root(device, boot_services, |root| {
+        for (kind_name, kind) in candidates {
+            let (EntryKind::Chainload(path) | EntryKind::Kernel(path)) = kind else {
+                continue;
+            };
+            if exists(root, path) {
+                println!("Found {} on a new volume", path);
+                let title = volume_title(device, kind_name, boot_services, titles);
+                let mut entry = BootEntry::new(title, kind);
+                entry.device = Some(device);
+                add(entry);
+            }
+        }
+    });
+    if let Err(e) = res {
+        println!("Error reading a new volume: {}", e);
+    }
+}
+
+/// `<label> (<kind_name>)`, or `<device node> (<kind_name>)` for volumes without a label
+fn volume_title<'a>(
+    device: Handle,
+    kind_name: &str,
+    boot_services: &BootServices,
+    titles: &mut Titles<'a>,
+) -> &'a str {
+    let mut label_buf = [0_u16; MAX_LABEL_LEN];
+    let label = SimpleFileSystemProtocol::open(device, boot_services)
+        .ok()
+        .and_then(|fs| fs.volume_label(&mut label_buf).ok())
+        .filter(|label| !label.is_empty());
+    let title = match label {
+        Some(label) => titles.push(format_args!("{} ({})", label, kind_name)),
+        // The last node before the partition is the disk's bus (SATA, NVMe, USB...)
+        None => DevicePathProtocol::open(device, boot_services)
+            .ok()
+            .and_then(|path| {
+                let node = path
+                    .nodes()
+                    .filter(|n| matches!(n.node_type, HARDWARE_TYPE | ACPI_TYPE | MESSAGING_TYPE));
+                titles.push(format_args!("{} ({})", node.last()?, kind_name))
+            }),
+    };
+    title.unwrap_or("New volume")
+}
+
+/// Storage for the titles of discovered entries, which outlive the scan of their volume
+pub struct Titles<'a>(&'a mut [u8]);
+
+impl<'a> Titles<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self(buf)
+    }
+
+    /// Formats a title into the remaining storage, truncated if it doesn't fit. Returns `None`
+    /// once the storage is full.
+    fn push(&mut self, args: fmt::Arguments) -> Option<&'a str> {
+        let mut writer = TitleWriter {
+            buf: mem::take(&mut self.0),
+            len: 0,
+        };
+        // Errors only mean the title got truncated
+        let _ = writer.write_fmt(args);
+        let (title, rest) = writer.buf.split_at_mut(writer.len);
+        self.0 = rest;
+        // Safety: Only whole UTF-8 encoded chars were written
+        let title = unsafe { str::from_utf8_unchecked(title) };
+        (!title.is_empty()).then_some(title)
+    }
+}
+
+struct TitleWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for TitleWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            let dst = self
+                .buf
+                .get_mut(self.len..self.len + c.len_utf8())
+                .ok_or(fmt::Error)?;
+            c.encode_utf8(dst);
+            self.len += c.len_utf8();
+        }
+        Ok(())
+    }
+}
+
+fn exists(root: &FileProtocol, path: &str) -> bool {
+    let mut path_buf = [0_u16; MAX_PATH_LEN];
+    let Some(path) = path_to_cstr16(path, &mut path_buf) else {
+        return false;
+    };
+    match root.open(path, FileMode::Read, FileAttribute::default()) {
+        Ok(file) => {
+            // Safety: The file isn't used past this point
+            let _ = unsafe { file.close() };
+            true
+        }
+        Err(_) => false,
+    }
+}
@@ -25,15 +25,37 @@ pub fn protocol_derive(input: TokenStream) -> TokenStream {
 
     quote! {
         impl crate::uefi::protocols::Protocol for #ident {
-            fn try_locate(
+            const GUID: crate::uefi::Guid =
+                <#field_type as crate::uefi::protocols::RawProtocol>::GUID;
+
+            fn open(
                 handle: crate::uefi::Handle,
-                boot_services: &crate::uefi::BootServices
-            ) -> Result<&Self, crate::uefi::protocols::ProtocolLocateError> {
-                let raw = #field_type::try_locate_protocol(boot_services, handle)?;
-                unsafe { Ok(&*(raw as *const Self)) }
+                boot_services: &crate::uefi::boot_services::BootServices,
+            ) -> Result<
+                crate::uefi::protocols::ScopedProtocol<'_, Self>,
+                crate::uefi::protocols::ProtocolLocateError,
+            > {
+                boot_services.open_protocol::<Self>(
+                    handle,
+                    crate::uefi::protocols::OpenProtocolAttributes::ByHandleProtocol,
+                )
             }
-        }
 
+            fn open_exclusive(
+                handle: crate::uefi::Handle,
+                boot_services: &crate::uefi::boot_services::BootServices,
+            ) -> Result<
+                crate::uefi::protocols::ExclusiveProtocol<'_, Self>,
+                crate::uefi::protocols::ProtocolLocateError,
+            > {
+                boot_services
+                    .open_protocol::<Self>(
+                        handle,
+                        crate::uefi::protocols::OpenProtocolAttributes::Exclusive,
+                    )
+                    .map(crate::uefi::protocols::ExclusiveProtocol)
+            }
+        }
     }
     .into()
 }